@@ -0,0 +1,237 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use dashu_float::DBig;
+use dashu_ratio::RBig;
+use num_complex::Complex64;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::calculator::{format_number, Equation, Functions, Item};
+use crate::complex::{format_complex, Complex, ComplexDisplay};
+use crate::currency::Rates;
+use crate::decimal::{format_decimal, Decimal};
+use crate::programmer::{format_integer, Integer, Radix, Word};
+use crate::rational::{format_fraction, FractionDisplay, Rational};
+use crate::solver::{solve_with, Float, Memory, SolveError, Stored};
+use crate::units::solve_units;
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Precision {
+    Float,
+    //number of significant digits
+    Decimal(usize),
+    //exact fractions, falling back to Float when the answer can't be exact
+    Rational,
+    //complex floats
+    Complex,
+    //64 bit integers for programmer mode, shown in the radix
+    Integer(Radix, Word),
+}
+
+//the most significant digits Decimal can be asked for
+pub const MAX_DIGITS: usize = 1000;
+
+//how answers that can be written more than one way are shown
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct AnswerFormat {
+    pub fraction: FractionDisplay,
+    pub complex: ComplexDisplay,
+    //polar angles
+    pub degrees: bool,
+    //integers are shown in the radix they were solved in when this is none
+    pub radix: Option<Radix>,
+}
+
+//values saved under a name, like x = 3.5
+pub type Variables = BTreeMap<String, Answer>;
+
+//saved tagged like Float(2.5), see parse_saved for the bare floats saved before
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Answer {
+    Float(f64),
+    Decimal {
+        value: DBig,
+        digits: usize,
+    },
+    Rational(RBig),
+    Complex(Complex64),
+    //the value is the word's bits, overflowed is set when something wrapped around
+    Integer {
+        value: i64,
+        radix: Radix,
+        word: Word,
+        overflowed: bool,
+    },
+    //a value measured in the unit, like 3.29 mi
+    Quantity {
+        value: f64,
+        unit: String,
+    },
+}
+
+//reads saved history or variables, falling back to the legacy form from before answers were tagged when every answer was a bare float
+pub fn parse_saved<T: DeserializeOwned, L: DeserializeOwned>(
+    text: &str,
+    legacy: impl FnOnce(L) -> T,
+) -> Option<T> {
+    ron::from_str(text)
+        .ok()
+        .or_else(|| ron::from_str(text).ok().map(legacy))
+}
+
+impl Answer {
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Answer::Float(value) => *value,
+            Answer::Decimal { value, .. } => value.to_f64().value(),
+            Answer::Rational(value) => value.to_f64().value(),
+            Answer::Complex(value) => value.re,
+            Answer::Integer { value, word, .. } => word.value(*value as u64) as f64,
+            Answer::Quantity { value, .. } => *value,
+        }
+    }
+
+    //the full value in a form every backend can parse, used for Ans
+    pub fn to_ans(&self) -> String {
+        match self {
+            Answer::Float(value) => value.to_string(),
+            Answer::Decimal { value, .. } => {
+                format!("{}e{}", value.repr().significand(), value.repr().exponent())
+            }
+            Answer::Rational(value) => format_fraction(value, FractionDisplay::Fraction),
+            Answer::Complex(value) if value.im == 0.0 => value.re.to_string(),
+            Answer::Complex(value) => format!("{}{:+}i", value.re, value.im),
+            Answer::Integer { value, word, .. } => word.value(*value as u64).to_string(),
+            //the unit is stored separately, see to_stored
+            Answer::Quantity { value, .. } => value.to_string(),
+        }
+    }
+
+    //units written with the base units like kg·m²/s² can't be typed, so only their number is kept
+    fn to_stored(&self) -> Stored {
+        let unit = match self {
+            Answer::Quantity { unit, .. } => Equation::parse(unit).ok(),
+            _ => None,
+        };
+        Stored {
+            number: self.to_ans(),
+            unit,
+        }
+    }
+
+    //the answer as an equation to carry on from, quantities keep their unit so 3.1 mi can go on with + 1 mi
+    pub fn to_equation(&self) -> Equation {
+        let mut equation = Equation::new();
+        match self {
+            Answer::Quantity { value, unit } => {
+                equation.try_push(Item::Rnd(format_number(*value)));
+                //units written with the base units like kg·m²/s² can't be typed, so they're left off
+                equation.try_push_text(unit).ok();
+            }
+            _ => {
+                equation.try_push(Item::Rnd(self.to_string()));
+            }
+        }
+        equation
+    }
+
+    pub fn is_fraction(&self) -> bool {
+        matches!(self, Answer::Rational(value) if !value.is_int())
+    }
+
+    pub fn is_complex(&self) -> bool {
+        matches!(self, Answer::Complex(value) if value.im != 0.0)
+    }
+
+    pub fn format(&self, format: AnswerFormat) -> String {
+        match self {
+            Answer::Rational(value) => format_fraction(value, format.fraction),
+            Answer::Complex(value) => format_complex(*value, format.complex, format.degrees),
+            Answer::Integer {
+                value, radix, word, ..
+            } => format_integer(*value, format.radix.unwrap_or(*radix), *word),
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Float(value) => write!(f, "{}", format_number(*value)),
+            Answer::Decimal { value, digits } => write!(f, "{}", format_decimal(value, *digits)),
+            Answer::Rational(value) => {
+                write!(f, "{}", format_fraction(value, FractionDisplay::Fraction))
+            }
+            Answer::Complex(value) => write!(
+                f,
+                "{}",
+                format_complex(*value, ComplexDisplay::Rectangular, false)
+            ),
+            Answer::Integer {
+                value, radix, word, ..
+            } => write!(f, "{}", format_integer(*value, *radix, *word)),
+            Answer::Quantity { value, unit } => write!(f, "{} {unit}", format_number(*value)),
+        }
+    }
+}
+
+pub fn solve_answer(
+    equation: &Equation,
+    degrees: bool,
+    ans: Option<&Answer>,
+    variables: &Variables,
+    functions: &Functions,
+    rates: &Rates,
+    precision: Precision,
+) -> Result<Answer, SolveError> {
+    let memory = Memory {
+        rates: rates.clone(),
+        ..memory(ans, variables, functions)
+    };
+    //units are measured with floats whatever the precision
+    if equation.has_units(&memory) {
+        return solve_units(equation, degrees, &memory).map(|(value, unit)| match unit {
+            Some(unit) => Answer::Quantity { value, unit },
+            None => Answer::Float(value),
+        });
+    }
+    match precision {
+        Precision::Float => solve_with(&Float, equation, degrees, &memory).map(Answer::Float),
+        Precision::Decimal(digits) => solve_with(&Decimal { digits }, equation, degrees, &memory)
+            .map(|value| Answer::Decimal { value, digits }),
+        Precision::Rational => match solve_with(&Rational, equation, degrees, &memory) {
+            Err(SolveError::Inexact(..)) => {
+                solve_with(&Float, equation, degrees, &memory).map(Answer::Float)
+            }
+            answer => answer.map(Answer::Rational),
+        },
+        Precision::Complex => solve_with(&Complex, equation, degrees, &memory).map(Answer::Complex),
+        Precision::Integer(radix, word) => {
+            let integer = Integer::new(word);
+            solve_with(&integer, equation, degrees, &memory).map(|value| Answer::Integer {
+                value: value as i64,
+                radix,
+                word,
+                overflowed: integer.overflowed(),
+            })
+        }
+    }
+}
+
+//Ans is 0 until something has been solved
+pub(crate) fn memory(ans: Option<&Answer>, variables: &Variables, functions: &Functions) -> Memory {
+    Memory {
+        ans: ans.map(Answer::to_stored).unwrap_or(Stored {
+            number: "0".into(),
+            unit: None,
+        }),
+        variables: variables
+            .iter()
+            .map(|(name, value)| (name.clone(), value.to_stored()))
+            .collect(),
+        functions: functions.clone(),
+        ..Default::default()
+    }
+}
@@ -1,29 +1,77 @@
+use std::collections::BTreeMap;
+
+use calculator::answer::parse_saved;
+use calculator::parser;
+use calculator::programmer::{format_integer, Word};
+use calculator::solver::SolveError;
+use calculator::statistics::parse_values;
+use calculator::Item::*;
+use calculator::{
+    exact_form, format_number, load_rates, push_history, solve_answer, Answer, AnswerFormat,
+    ComplexDisplay, Distribution, Equation, FractionDisplay, Function, Functions, Item, Precision,
+    Query, Radix, Rates, RatesError, Summary, Target, Variables, MAX_DIGITS,
+};
 use eframe::epaint::Shadow;
 use eframe::*;
 use egui::{text::LayoutJob, *};
 use egui_extras::RetainedImage;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 
-use crate::calculator::Equation;
-use crate::calculator::Item::*;
 use crate::calculator_button::CalculatorButton;
-use crate::solver;
 pub struct Calculator {
     history_icon: RetainedImage,
     degrees: bool,
     inverse: bool,
+    second_page: bool,
     equation: Equation,
-    history: Vec<(Equation, f64)>,
+    history: Vec<(Equation, Answer)>,
+    history_limit: usize,
+    mode: Mode,
+    //the radix programmer mode types and shows numbers in
+    radix: Radix,
+    word: Word,
+    digits: usize,
+    fraction_display: FractionDisplay,
+    complex_display: ComplexDisplay,
+    exact_form: Option<String>,
+    variables: Variables,
+    functions: Functions,
+    variable_name: String,
     previous_answer_state: PreviousAnswerState,
     animation_time: Option<f32>,
     show_history_menu: bool,
+    //the list of numbers typed into the statistics panel
+    statistics: String,
+    show_statistics_menu: bool,
+    distribution: Distribution,
+    distribution_query: Query,
+    distribution_parameters: Vec<f64>,
+    //the value, or the probability for the inverse cdf
+    distribution_x: f64,
+    //the json or csv file exchange rates are read from
+    rates_path: String,
+    rates: Rates,
+    rates_error: Option<RatesError>,
 }
 
 #[derive(PartialEq, Debug)]
 enum PreviousAnswerState {
     Show,
     Hide,
-    Error(Equation),
+    Error(Equation, SolveError),
+    PasteError(String),
+    //a pasted function definition
+    Defined(String),
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+enum Mode {
+    Float,
+    HighPrecision,
+    Exact,
+    Complex,
+    Programmer,
 }
 
 pub const FUNCTION_COLOR: Color32 = Color32::from_rgb(218, 220, 224);
@@ -35,10 +83,26 @@ pub const FONT_SIZE: f32 = 23.0;
 pub const GRID_SPACING: f32 = 7.5;
 pub const EQUATION_SIZE: f32 = 39.0;
 pub const PREVIOUS_SIZE: f32 = 22.0;
+pub const EXACT_SIZE: f32 = 28.0;
 pub const TITLE_BAR_HEIGHT: f32 = 60.0;
 
 pub const ANIMATION_DURATION: f32 = 0.14;
 
+pub const DEFAULT_HISTORY_LIMIT: usize = 100;
+const HISTORY_KEY: &str = "history";
+const HISTORY_LIMIT_KEY: &str = "history limit";
+pub const DEFAULT_DIGITS: usize = 32;
+const MODE_KEY: &str = "mode";
+const RADIX_KEY: &str = "radix";
+const WORD_KEY: &str = "word";
+const DIGITS_KEY: &str = "digits";
+const FRACTION_DISPLAY_KEY: &str = "fraction display";
+const COMPLEX_DISPLAY_KEY: &str = "complex display";
+const VARIABLES_KEY: &str = "variables";
+const FUNCTIONS_KEY: &str = "functions";
+const STATISTICS_KEY: &str = "statistics";
+const RATES_PATH_KEY: &str = "rates path";
+
 pub const ROUNDING: Rounding = {
     let rounding = 6.5;
     Rounding {
@@ -54,6 +118,21 @@ impl App for Calculator {
         [0.0, 0.0, 0.0, 0.0]
     }
 
+    fn save(&mut self, storage: &mut dyn Storage) {
+        eframe::set_value(storage, HISTORY_KEY, &self.history);
+        eframe::set_value(storage, HISTORY_LIMIT_KEY, &self.history_limit);
+        eframe::set_value(storage, MODE_KEY, &self.mode);
+        eframe::set_value(storage, RADIX_KEY, &self.radix);
+        eframe::set_value(storage, WORD_KEY, &self.word);
+        eframe::set_value(storage, DIGITS_KEY, &self.digits);
+        eframe::set_value(storage, FRACTION_DISPLAY_KEY, &self.fraction_display);
+        eframe::set_value(storage, COMPLEX_DISPLAY_KEY, &self.complex_display);
+        eframe::set_value(storage, VARIABLES_KEY, &self.variables);
+        eframe::set_value(storage, FUNCTIONS_KEY, &self.functions);
+        eframe::set_value(storage, STATISTICS_KEY, &self.statistics);
+        eframe::set_value(storage, RATES_PATH_KEY, &self.rates_path);
+    }
+
     fn update(&mut self, ctx: &Context, frame: &mut eframe::Frame) {
         self.handle_key_presses(ctx);
         TopBottomPanel::top("top panel")
@@ -83,6 +162,10 @@ impl App for Calculator {
                     rect
                 };
                 Self::title_bar_ui(ctx, ui, frame, title_bar_rect);
+                self.settings_menu(ctx);
+                self.variables_menu(ctx);
+                self.page_toggle(ctx);
+                self.distributions_menu(ctx);
 
                 egui::containers::Frame::none()
                     .stroke(Stroke::new(2.0, FUNCTION_COLOR))
@@ -120,8 +203,11 @@ impl App for Calculator {
                 self.buttons(ui);
             });
         self.show_history(ctx);
+        self.show_statistics(ctx);
         self.show_previous(ctx);
         self.show_current(ctx);
+        self.show_radixes(ctx);
+        self.show_rates_updated(ctx);
 
         if let Some(time) = &mut self.animation_time {
             if *time < ANIMATION_DURATION {
@@ -209,7 +295,73 @@ impl Calculator {
         };
         ctx.set_style(style);
 
-        Self {
+        let history_limit = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, HISTORY_LIMIT_KEY))
+            .unwrap_or(DEFAULT_HISTORY_LIMIT);
+        let mut history: Vec<(Equation, Answer)> = cc
+            .storage
+            .and_then(|storage| storage.get_string(HISTORY_KEY))
+            .and_then(|text| {
+                parse_saved(&text, |history: Vec<(Equation, f64)>| {
+                    history
+                        .into_iter()
+                        .map(|(equation, value)| (equation, Answer::Float(value)))
+                        .collect()
+                })
+            })
+            .unwrap_or_default();
+        history.drain(..history.len().saturating_sub(history_limit));
+        let mode = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, MODE_KEY))
+            .unwrap_or(Mode::Float);
+        let radix = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, RADIX_KEY))
+            .unwrap_or_default();
+        let word = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, WORD_KEY))
+            .unwrap_or_default();
+        let digits = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, DIGITS_KEY))
+            .unwrap_or(DEFAULT_DIGITS);
+        let fraction_display = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, FRACTION_DISPLAY_KEY))
+            .unwrap_or_default();
+        let complex_display = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, COMPLEX_DISPLAY_KEY))
+            .unwrap_or_default();
+        let variables = cc
+            .storage
+            .and_then(|storage| storage.get_string(VARIABLES_KEY))
+            .and_then(|text| {
+                parse_saved(&text, |variables: BTreeMap<String, f64>| {
+                    variables
+                        .into_iter()
+                        .map(|(name, value)| (name, Answer::Float(value)))
+                        .collect()
+                })
+            })
+            .unwrap_or_default();
+        let functions = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, FUNCTIONS_KEY))
+            .unwrap_or_default();
+        let statistics = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, STATISTICS_KEY))
+            .unwrap_or_default();
+        let rates_path = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, RATES_PATH_KEY))
+            .unwrap_or_default();
+
+        let mut calculator = Self {
             degrees: true,
             inverse: false,
             history_icon: RetainedImage::from_svg_bytes(
@@ -217,15 +369,59 @@ impl Calculator {
                 include_bytes!("..\\assets\\History Icon.svg"),
             )
             .unwrap(),
+            second_page: false,
             equation: Equation::new(),
-            history: vec![],
+            history,
+            history_limit,
+            mode,
+            radix,
+            word,
+            digits,
+            fraction_display,
+            complex_display,
+            exact_form: None,
+            variables,
+            functions,
+            variable_name: String::new(),
             previous_answer_state: PreviousAnswerState::Hide,
             animation_time: None,
             show_history_menu: false,
+            statistics,
+            show_statistics_menu: false,
+            distribution: Distribution::default(),
+            distribution_query: Query::default(),
+            distribution_parameters: default_parameters(Distribution::default()),
+            distribution_x: 0.0,
+            rates_path,
+            rates: Rates::default(),
+            rates_error: None,
+        };
+        calculator.reload_rates();
+        calculator
+    }
+
+    //read again every time so rates can be updated without restarting
+    fn reload_rates(&mut self) {
+        if self.rates_path.trim().is_empty() {
+            self.rates = Rates::default();
+            self.rates_error = None;
+            return;
+        }
+        match load_rates(self.rates_path.trim().as_ref()) {
+            Ok(rates) => {
+                self.rates = rates;
+                self.rates_error = None;
+            }
+            Err(error) => self.rates_error = Some(error),
         }
     }
 
     fn handle_key_presses(&mut self, ctx: &Context) {
+        //typing a variable name
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+
         let keys = ctx.input(|i| {
             i.raw
                 .events
@@ -240,11 +436,79 @@ impl Calculator {
                 .collect::<Vec<_>>()
         });
 
+        let pastes = ctx.input(|i| {
+            i.raw
+                .events
+                .iter()
+                .filter_map(|item| {
+                    if let Event::Paste(text) = item {
+                        Some(text.clone())
+                    } else {
+                        None
+                    }
+                })
+                .collect::<Vec<_>>()
+        });
+
+        for paste in pastes {
+            let text = parser::normalize_pasted(&paste);
+            //x = 3.5 is solved straight away and saved, f(x) = x^2 is saved for later
+            match Equation::parse_assignment(&text) {
+                Ok((Some(Target::Variable(name)), equation)) => {
+                    self.equation = equation;
+                    self.previous_answer_state = PreviousAnswerState::Hide;
+                    self.solve();
+                    if self.previous_answer_state == PreviousAnswerState::Show {
+                        let answer = self.history.last().unwrap().1.clone();
+                        self.variables.insert(name, answer);
+                    }
+                    self.show_history_menu = false;
+                    self.show_statistics_menu = false;
+                    continue;
+                }
+                Ok((Some(Target::Function(name, parameters)), body)) => {
+                    let function = Function { parameters, body };
+                    self.previous_answer_state =
+                        PreviousAnswerState::Defined(function.to_text(&name));
+                    self.functions.insert(name, function);
+                    self.show_history_menu = false;
+                    self.show_statistics_menu = false;
+                    continue;
+                }
+                _ => {}
+            }
+            self.try_continue_answer();
+            self.previous_answer_state = match self.equation.try_push_text(&text) {
+                Ok(()) => PreviousAnswerState::Hide,
+                Err(error) => PreviousAnswerState::PasteError(format!(
+                    "Couldn't paste \"{}\"",
+                    &text[error.span()]
+                )),
+            };
+            self.show_history_menu = false;
+            self.show_statistics_menu = false;
+        }
+
         for key in keys {
             let item = match key.as_str() {
-                "1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9" | "0" | "." => {
-                    Number(key.into())
+                //letters are digits when typing hexadecimal
+                "a" | "b" | "c" | "d" | "e" | "f" | "A" | "B" | "C" | "D" | "E" | "F"
+                    if self.mode == Mode::Programmer && self.radix == Radix::Hexadecimal =>
+                {
+                    Number(format!("{}{}", self.radix.prefix(), key.to_uppercase()))
+                }
+                "1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9" | "0"
+                    if self.mode == Mode::Programmer =>
+                {
+                    Number(format!("{}{key}", self.radix.prefix()))
                 }
+                "." if self.mode == Mode::Programmer => continue,
+                "&" if self.mode == Mode::Programmer => And,
+                "|" if self.mode == Mode::Programmer => Or,
+                "~" if self.mode == Mode::Programmer => Not,
+                "<" if self.mode == Mode::Programmer => LeftShift,
+                ">" if self.mode == Mode::Programmer => RightShift,
+                "1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9" | "0" | "." => Number(key),
                 "+" => {
                     self.try_continue_answer();
                     Add
@@ -261,11 +525,26 @@ impl Calculator {
                     self.try_continue_answer();
                     Divide
                 }
+                "m" => {
+                    self.try_continue_answer();
+                    Modulo
+                }
+                "\\" => {
+                    self.try_continue_answer();
+                    IntegerDivide
+                }
+                //brackets look like the corners of ⌊x⌋ and ⌈x⌉
+                "[" => Floor,
+                "]" => Ceil,
+                "u" => Trunc,
+                "|" => Abs,
+                "n" => Sign,
                 "!" => Factorial,
                 "%" => Percent,
                 "^" => Power,
                 "(" => OpeningParenthesis,
                 ")" => ClosingParenthesis,
+                "," => Comma,
                 "q" => Sqrt,
                 "e" => E,
                 "r" => Nroot,
@@ -278,6 +557,8 @@ impl Calculator {
                     }
                 }
                 "p" => Pi,
+                //i already toggles inverse
+                "j" => I,
                 "a" => Ans,
                 "s" => {
                     if self.inverse {
@@ -297,6 +578,31 @@ impl Calculator {
                         Cos
                     }
                 }
+                //shift makes the trigonometric shortcuts hyperbolic
+                "S" => {
+                    if self.inverse {
+                        self.inverse = false;
+                        Asinh
+                    } else {
+                        Sinh
+                    }
+                }
+                "C" => {
+                    if self.inverse {
+                        self.inverse = false;
+                        Acosh
+                    } else {
+                        Cosh
+                    }
+                }
+                "T" => {
+                    if self.inverse {
+                        self.inverse = false;
+                        Atanh
+                    } else {
+                        Tanh
+                    }
+                }
                 "i" => {
                     self.inverse = !self.inverse;
                     continue;
@@ -306,20 +612,38 @@ impl Calculator {
             self.equation.try_push(item);
             self.previous_answer_state = PreviousAnswerState::Hide;
             self.show_history_menu = false;
+            self.show_statistics_menu = false;
+        }
+
+        if ctx.input(|i| i.events.contains(&Event::Copy)) {
+            let text = match &self.previous_answer_state {
+                PreviousAnswerState::Show => self.history.last().unwrap().1.to_string(),
+                PreviousAnswerState::Hide
+                | PreviousAnswerState::PasteError(..)
+                | PreviousAnswerState::Defined(..) => self.equation.to_ascii(),
+                PreviousAnswerState::Error(equation, _) => equation.to_ascii(),
+            };
+            ctx.output_mut(|o| o.copied_text = text);
         }
 
         if ctx.input(|i| i.key_pressed(Key::Backspace)) {
             self.equation.backspace();
             self.previous_answer_state = PreviousAnswerState::Hide;
             self.show_history_menu = false;
+            self.show_statistics_menu = false;
         }
         if ctx.input(|i| i.key_pressed(Key::Enter)) {
             self.solve();
             self.show_history_menu = false;
+            self.show_statistics_menu = false;
         }
     }
 
     fn buttons(&mut self, ui: &mut Ui) {
+        if self.mode == Mode::Programmer {
+            self.programmer_buttons(ui);
+            return;
+        }
         ui.vertical(|ui| {
             ui.spacing_mut().item_spacing = vec2(GRID_SPACING, GRID_SPACING);
             ui.horizontal(|ui| {
@@ -345,14 +669,29 @@ impl Calculator {
                     self.equation.try_push(ClosingParenthesis);
                     self.previous_answer_state = PreviousAnswerState::Hide;
                 }
-                if CalculatorButton::new("%", FUNCTION_COLOR)
-                    .ui(ui)
-                    .clicked_or_drag_ended()
-                {
-                    self.equation.try_push(Percent);
-                    self.previous_answer_state = PreviousAnswerState::Hide;
+                if self.second_page {
+                    if CalculatorButton::new(",", FUNCTION_COLOR)
+                        .ui(ui)
+                        .clicked_or_drag_ended()
+                    {
+                        self.equation.try_push(Comma);
+                        self.previous_answer_state = PreviousAnswerState::Hide;
+                    }
+                } else {
+                    if CalculatorButton::new("%", FUNCTION_COLOR)
+                        .ui(ui)
+                        .clicked_or_drag_ended()
+                    {
+                        self.equation.try_push(Percent);
+                        self.previous_answer_state = PreviousAnswerState::Hide;
+                    }
                 }
-                if self.previous_answer_state == PreviousAnswerState::Hide {
+                if matches!(
+                    self.previous_answer_state,
+                    PreviousAnswerState::Hide
+                        | PreviousAnswerState::PasteError(..)
+                        | PreviousAnswerState::Defined(..)
+                ) {
                     if CalculatorButton::new("CE", FUNCTION_COLOR)
                         .ui(ui)
                         .clicked_or_drag_ended()
@@ -386,43 +725,48 @@ impl Calculator {
                     self.inverse = !self.inverse;
                 }
 
-                if self.inverse {
-                    if CalculatorButton::new(superscript(ui, "sin", "-1"), FUNCTION_COLOR)
-                        .ui(ui)
-                        .clicked_or_drag_ended()
-                    {
-                        self.equation.try_push(Asin);
-                        self.inverse = false;
-                        self.previous_answer_state = PreviousAnswerState::Hide;
-                    };
+                if self.second_page {
+                    self.hyperbolic_button(ui, "sinh", Sinh, Asinh);
+                    self.hyperbolic_button(ui, "cosh", Cosh, Acosh);
                 } else {
-                    if CalculatorButton::new("sin", FUNCTION_COLOR)
-                        .ui(ui)
-                        .clicked_or_drag_ended()
-                    {
-                        self.equation.try_push(Sin);
-                        self.previous_answer_state = PreviousAnswerState::Hide;
+                    if self.inverse {
+                        if CalculatorButton::new(superscript(ui, "sin", "-1"), FUNCTION_COLOR)
+                            .ui(ui)
+                            .clicked_or_drag_ended()
+                        {
+                            self.equation.try_push(Asin);
+                            self.inverse = false;
+                            self.previous_answer_state = PreviousAnswerState::Hide;
+                        };
+                    } else {
+                        if CalculatorButton::new("sin", FUNCTION_COLOR)
+                            .ui(ui)
+                            .clicked_or_drag_ended()
+                        {
+                            self.equation.try_push(Sin);
+                            self.previous_answer_state = PreviousAnswerState::Hide;
+                        }
                     }
-                }
 
-                if self.inverse {
-                    if CalculatorButton::new(superscript(ui, "e", "x"), FUNCTION_COLOR)
-                        .ui(ui)
-                        .clicked_or_drag_ended()
-                    {
-                        if self.equation.try_push(E) {
-                            self.equation.try_push(Power);
+                    if self.inverse {
+                        if CalculatorButton::new(superscript(ui, "e", "x"), FUNCTION_COLOR)
+                            .ui(ui)
+                            .clicked_or_drag_ended()
+                        {
+                            if self.equation.try_push(E) {
+                                self.equation.try_push(Power);
+                            }
+                            self.inverse = false;
+                            self.previous_answer_state = PreviousAnswerState::Hide;
+                        }
+                    } else {
+                        if CalculatorButton::new("ln", FUNCTION_COLOR)
+                            .ui(ui)
+                            .clicked_or_drag_ended()
+                        {
+                            self.equation.try_push(Ln);
+                            self.previous_answer_state = PreviousAnswerState::Hide;
                         }
-                        self.inverse = false;
-                        self.previous_answer_state = PreviousAnswerState::Hide;
-                    }
-                } else {
-                    if CalculatorButton::new("ln", FUNCTION_COLOR)
-                        .ui(ui)
-                        .clicked_or_drag_ended()
-                    {
-                        self.equation.try_push(Ln);
-                        self.previous_answer_state = PreviousAnswerState::Hide;
                     }
                 }
                 if CalculatorButton::new("7", NUMBER_COLOR)
@@ -456,50 +800,55 @@ impl Calculator {
                 }
             });
             ui.horizontal(|ui| {
-                if CalculatorButton::new("π", FUNCTION_COLOR)
-                    .ui(ui)
-                    .clicked_or_drag_ended()
-                {
-                    self.equation.try_push(Pi);
-                    self.previous_answer_state = PreviousAnswerState::Hide;
-                }
-
-                if self.inverse {
-                    if CalculatorButton::new(superscript(ui, "cos", "-1"), FUNCTION_COLOR)
-                        .ui(ui)
-                        .clicked_or_drag_ended()
-                    {
-                        self.equation.try_push(Acos);
-                        self.inverse = false;
-                        self.previous_answer_state = PreviousAnswerState::Hide;
-                    }
+                if self.second_page {
+                    self.hyperbolic_button(ui, "tanh", Tanh, Atanh);
+                    self.second_page_buttons(ui, &[("min", Min), ("max", Max)]);
                 } else {
-                    if CalculatorButton::new("cos", FUNCTION_COLOR)
+                    if CalculatorButton::new("π", FUNCTION_COLOR)
                         .ui(ui)
                         .clicked_or_drag_ended()
                     {
-                        self.equation.try_push(Cos);
+                        self.equation.try_push(Pi);
                         self.previous_answer_state = PreviousAnswerState::Hide;
                     }
-                }
-                if self.inverse {
-                    if CalculatorButton::new(superscript(ui, "x", "10"), FUNCTION_COLOR)
-                        .ui(ui)
-                        .clicked_or_drag_ended()
-                    {
-                        if self.equation.try_push(Power) {
-                            self.equation.try_push(Number("10".into()));
+
+                    if self.inverse {
+                        if CalculatorButton::new(superscript(ui, "cos", "-1"), FUNCTION_COLOR)
+                            .ui(ui)
+                            .clicked_or_drag_ended()
+                        {
+                            self.equation.try_push(Acos);
+                            self.inverse = false;
+                            self.previous_answer_state = PreviousAnswerState::Hide;
+                        }
+                    } else {
+                        if CalculatorButton::new("cos", FUNCTION_COLOR)
+                            .ui(ui)
+                            .clicked_or_drag_ended()
+                        {
+                            self.equation.try_push(Cos);
+                            self.previous_answer_state = PreviousAnswerState::Hide;
                         }
-                        self.inverse = false;
-                        self.previous_answer_state = PreviousAnswerState::Hide;
                     }
-                } else {
-                    if CalculatorButton::new("log", FUNCTION_COLOR)
-                        .ui(ui)
-                        .clicked_or_drag_ended()
-                    {
-                        self.equation.try_push(Log);
-                        self.previous_answer_state = PreviousAnswerState::Hide;
+                    if self.inverse {
+                        if CalculatorButton::new(superscript(ui, "x", "10"), FUNCTION_COLOR)
+                            .ui(ui)
+                            .clicked_or_drag_ended()
+                        {
+                            if self.equation.try_push(Power) {
+                                self.equation.try_push(Number("10".into()));
+                            }
+                            self.inverse = false;
+                            self.previous_answer_state = PreviousAnswerState::Hide;
+                        }
+                    } else {
+                        if CalculatorButton::new("log", FUNCTION_COLOR)
+                            .ui(ui)
+                            .clicked_or_drag_ended()
+                        {
+                            self.equation.try_push(Log);
+                            self.previous_answer_state = PreviousAnswerState::Hide;
+                        }
                     }
                 }
                 if CalculatorButton::new("4", NUMBER_COLOR)
@@ -533,50 +882,54 @@ impl Calculator {
                 }
             });
             ui.horizontal(|ui| {
-                if CalculatorButton::new("e", FUNCTION_COLOR)
-                    .ui(ui)
-                    .clicked_or_drag_ended()
-                {
-                    self.equation.try_push(E);
-                    self.previous_answer_state = PreviousAnswerState::Hide;
-                }
-                if self.inverse {
-                    if CalculatorButton::new(superscript(ui, "tan", "-1"), FUNCTION_COLOR)
-                        .ui(ui)
-                        .clicked_or_drag_ended()
-                    {
-                        self.equation.try_push(Atan);
-                        self.inverse = false;
-                        self.previous_answer_state = PreviousAnswerState::Hide;
-                    }
+                if self.second_page {
+                    self.second_page_buttons(ui, &[("gcd", Gcd), ("lcm", Lcm), ("mod", Mod)]);
                 } else {
-                    if CalculatorButton::new("tan", FUNCTION_COLOR)
+                    if CalculatorButton::new("e", FUNCTION_COLOR)
                         .ui(ui)
                         .clicked_or_drag_ended()
                     {
-                        self.equation.try_push(Tan);
+                        self.equation.try_push(E);
                         self.previous_answer_state = PreviousAnswerState::Hide;
                     }
-                }
-
-                if self.inverse {
-                    if CalculatorButton::new(superscript(ui, "x", "2"), FUNCTION_COLOR)
-                        .ui(ui)
-                        .clicked_or_drag_ended()
-                    {
-                        if self.equation.try_push(Power) {
-                            self.equation.try_push(Number("2".into()));
+                    if self.inverse {
+                        if CalculatorButton::new(superscript(ui, "tan", "-1"), FUNCTION_COLOR)
+                            .ui(ui)
+                            .clicked_or_drag_ended()
+                        {
+                            self.equation.try_push(Atan);
+                            self.inverse = false;
+                            self.previous_answer_state = PreviousAnswerState::Hide;
+                        }
+                    } else {
+                        if CalculatorButton::new("tan", FUNCTION_COLOR)
+                            .ui(ui)
+                            .clicked_or_drag_ended()
+                        {
+                            self.equation.try_push(Tan);
+                            self.previous_answer_state = PreviousAnswerState::Hide;
                         }
-                        self.inverse = false;
-                        self.previous_answer_state = PreviousAnswerState::Hide;
                     }
-                } else {
-                    if CalculatorButton::new("√", FUNCTION_COLOR)
-                        .ui(ui)
-                        .clicked_or_drag_ended()
-                    {
-                        self.equation.try_push(Sqrt);
-                        self.previous_answer_state = PreviousAnswerState::Hide;
+
+                    if self.inverse {
+                        if CalculatorButton::new(superscript(ui, "x", "2"), FUNCTION_COLOR)
+                            .ui(ui)
+                            .clicked_or_drag_ended()
+                        {
+                            if self.equation.try_push(Power) {
+                                self.equation.try_push(Number("2".into()));
+                            }
+                            self.inverse = false;
+                            self.previous_answer_state = PreviousAnswerState::Hide;
+                        }
+                    } else {
+                        if CalculatorButton::new("√", FUNCTION_COLOR)
+                            .ui(ui)
+                            .clicked_or_drag_ended()
+                        {
+                            self.equation.try_push(Sqrt);
+                            self.previous_answer_state = PreviousAnswerState::Hide;
+                        }
                     }
                 }
                 if CalculatorButton::new("1", NUMBER_COLOR)
@@ -610,97 +963,230 @@ impl Calculator {
                 }
             });
             ui.horizontal(|ui| {
-                if self.inverse {
-                    if CalculatorButton::new("Rnd", FUNCTION_COLOR)
+                if self.second_page {
+                    self.second_page_buttons(ui, &[("nCr", Ncr), ("nPr", Npr), ("round", Round)]);
+                } else {
+                    if self.inverse {
+                        if CalculatorButton::new("Rnd", FUNCTION_COLOR)
+                            .ui(ui)
+                            .clicked_or_drag_ended()
+                        {
+                            let random = rand::thread_rng().gen::<f64>().to_string();
+                            self.equation.try_push(Rnd(format!("{random:.7}")));
+                            self.inverse = false;
+                            self.previous_answer_state = PreviousAnswerState::Hide;
+                        }
+                    } else {
+                        if CalculatorButton::new("Ans", FUNCTION_COLOR)
+                            .ui(ui)
+                            .clicked_or_drag_ended()
+                        {
+                            self.equation.try_push(Ans);
+                            self.previous_answer_state = PreviousAnswerState::Hide;
+                        }
+                    }
+                    if CalculatorButton::new("EXP", FUNCTION_COLOR)
                         .ui(ui)
                         .clicked_or_drag_ended()
                     {
-                        let random = rand::thread_rng().gen::<f64>().to_string();
-                        self.equation.try_push(Rnd(format!("{random:.7}")));
-                        self.inverse = false;
+                        self.equation.try_push(EXP);
                         self.previous_answer_state = PreviousAnswerState::Hide;
                     }
-                } else {
-                    if CalculatorButton::new("Ans", FUNCTION_COLOR)
+
+                    if self.inverse {
+                        if CalculatorButton::new(
+                            {
+                                let mut job = LayoutJob::default();
+                                job.append(
+                                    "y",
+                                    1.0,
+                                    TextFormat {
+                                        font_id: FontId::new(
+                                            12.0,
+                                            FontFamily::Name("roboto".into()),
+                                        ),
+                                        valign: Align::TOP,
+                                        color: ui.visuals().text_color(),
+                                        ..Default::default()
+                                    },
+                                );
+                                job.append(
+                                    "√x",
+                                    0.0,
+                                    TextFormat {
+                                        font_id: FontId::new(
+                                            FONT_SIZE,
+                                            FontFamily::Name("roboto".into()),
+                                        ),
+                                        valign: Align::TOP,
+                                        color: ui.visuals().text_color(),
+                                        ..Default::default()
+                                    },
+                                );
+
+                                job
+                            },
+                            FUNCTION_COLOR,
+                        )
                         .ui(ui)
                         .clicked_or_drag_ended()
-                    {
-                        self.equation.try_push(Ans);
-                        self.previous_answer_state = PreviousAnswerState::Hide;
+                        {
+                            self.equation.try_push(Nroot);
+                            self.inverse = false;
+                            self.previous_answer_state = PreviousAnswerState::Hide;
+                        }
+                    } else {
+                        if CalculatorButton::new(superscript(ui, "x", "y"), FUNCTION_COLOR)
+                            .ui(ui)
+                            .clicked_or_drag_ended()
+                        {
+                            self.equation.try_push(Power);
+                            self.previous_answer_state = PreviousAnswerState::Hide;
+                        }
                     }
                 }
-                if CalculatorButton::new("EXP", FUNCTION_COLOR)
+
+                if CalculatorButton::new("0", NUMBER_COLOR)
+                    .ui(ui)
+                    .clicked_or_drag_ended()
+                {
+                    self.equation.try_push(Number("0".into()));
+                    self.previous_answer_state = PreviousAnswerState::Hide;
+                }
+                if CalculatorButton::new(".", NUMBER_COLOR)
                     .ui(ui)
                     .clicked_or_drag_ended()
                 {
-                    self.equation.try_push(EXP);
+                    self.equation.try_push(Number(".".into()));
                     self.previous_answer_state = PreviousAnswerState::Hide;
                 }
 
-                if self.inverse {
-                    if CalculatorButton::new(
-                        {
-                            let mut job = LayoutJob::default();
-                            job.append(
-                                "y",
-                                1.0,
-                                TextFormat {
-                                    font_id: FontId::new(12.0, FontFamily::Name("roboto".into())),
-                                    valign: Align::TOP,
-                                    color: ui.visuals().text_color(),
-                                    ..Default::default()
-                                },
-                            );
-                            job.append(
-                                "√x",
-                                0.0,
-                                TextFormat {
-                                    font_id: FontId::new(
-                                        FONT_SIZE,
-                                        FontFamily::Name("roboto".into()),
-                                    ),
-                                    valign: Align::TOP,
-                                    color: ui.visuals().text_color(),
-                                    ..Default::default()
-                                },
-                            );
+                if CalculatorButton::new(
+                    RichText::new("=").size(FONT_SIZE).color(Color32::WHITE),
+                    Color32::from_rgb(66, 133, 244),
+                )
+                .ui(ui)
+                .clicked_or_drag_ended()
+                {
+                    self.solve();
+                }
 
-                            job
+                if CalculatorButton::new("+", FUNCTION_COLOR)
+                    .ui(ui)
+                    .clicked_or_drag_ended()
+                {
+                    self.try_continue_answer();
+                    self.equation.try_push(Add);
+                    self.previous_answer_state = PreviousAnswerState::Hide;
+                }
+            });
+        });
+    }
+
+    //the digits of every radix, with the ones that aren't in the current radix disabled
+    fn programmer_buttons(&mut self, ui: &mut Ui) {
+        ui.vertical(|ui| {
+            ui.spacing_mut().item_spacing = vec2(GRID_SPACING, GRID_SPACING);
+            ui.horizontal(|ui| {
+                for radix in Radix::ALL {
+                    if CalculatorButton::new(
+                        radix.name(),
+                        if self.radix == radix {
+                            NUMBER_COLOR
+                        } else {
+                            FUNCTION_COLOR
                         },
-                        FUNCTION_COLOR,
                     )
                     .ui(ui)
                     .clicked_or_drag_ended()
                     {
-                        todo!();
-                        self.inverse = false;
+                        self.radix = radix;
+                    }
+                }
+                //the second page has the bitwise operators
+                if self.second_page {
+                    self.operation_button(ui, "<<", LeftShift);
+                    self.operation_button(ui, ">>", RightShift);
+                } else {
+                    self.second_page_buttons(
+                        ui,
+                        &[("(", OpeningParenthesis), (")", ClosingParenthesis)],
+                    );
+                }
+                if matches!(
+                    self.previous_answer_state,
+                    PreviousAnswerState::Hide
+                        | PreviousAnswerState::PasteError(..)
+                        | PreviousAnswerState::Defined(..)
+                ) {
+                    if CalculatorButton::new("CE", FUNCTION_COLOR)
+                        .ui(ui)
+                        .clicked_or_drag_ended()
+                    {
+                        self.equation.backspace();
                         self.previous_answer_state = PreviousAnswerState::Hide;
                     }
                 } else {
-                    if CalculatorButton::new(superscript(ui, "x", "y"), FUNCTION_COLOR)
+                    if CalculatorButton::new("AC", FUNCTION_COLOR)
                         .ui(ui)
                         .clicked_or_drag_ended()
                     {
-                        self.equation.try_push(Power);
+                        self.equation.clear();
                         self.previous_answer_state = PreviousAnswerState::Hide;
                     }
                 }
-
-                if CalculatorButton::new("0", NUMBER_COLOR)
+            });
+            ui.horizontal(|ui| {
+                self.digit_buttons(ui, &["A", "B"]);
+                if self.second_page {
+                    self.operation_button(ui, "AND", And);
+                } else {
+                    self.second_page_buttons(ui, &[("mod", Mod)]);
+                }
+                self.digit_buttons(ui, &["7", "8", "9"]);
+                self.operation_button(ui, "÷", Divide);
+            });
+            ui.horizontal(|ui| {
+                self.digit_buttons(ui, &["C", "D"]);
+                if self.second_page {
+                    self.operation_button(ui, "OR", Or);
+                } else if CalculatorButton::new(superscript(ui, "x", "y"), FUNCTION_COLOR)
                     .ui(ui)
                     .clicked_or_drag_ended()
                 {
-                    self.equation.try_push(Number("0".into()));
+                    self.equation.try_push(Power);
                     self.previous_answer_state = PreviousAnswerState::Hide;
                 }
-                if CalculatorButton::new(".", NUMBER_COLOR)
+                self.digit_buttons(ui, &["4", "5", "6"]);
+                self.operation_button(ui, "×", Multiply);
+            });
+            ui.horizontal(|ui| {
+                self.digit_buttons(ui, &["E", "F"]);
+                if self.second_page {
+                    self.operation_button(ui, "XOR", Xor);
+                } else {
+                    self.second_page_buttons(ui, &[("x!", Factorial)]);
+                }
+                self.digit_buttons(ui, &["1", "2", "3"]);
+                self.operation_button(ui, "–", Subtract);
+            });
+            ui.horizontal(|ui| {
+                if self.second_page {
+                    self.second_page_buttons(ui, &[("NOT", Not)]);
+                    self.operation_button(ui, "ROL", RotateLeft);
+                    self.operation_button(ui, "ROR", RotateRight);
+                } else {
+                    self.second_page_buttons(ui, &[("Ans", Ans), ("gcd", Gcd), (",", Comma)]);
+                }
+                if CalculatorButton::new("0", NUMBER_COLOR)
+                    .min_size(vec2(BUTTON_WIDTH * 2.0 + GRID_SPACING, BUTTON_HEIGHT))
                     .ui(ui)
                     .clicked_or_drag_ended()
                 {
-                    self.equation.try_push(Number(".".into()));
+                    self.equation
+                        .try_push(Number(format!("{}0", self.radix.prefix())));
                     self.previous_answer_state = PreviousAnswerState::Hide;
                 }
-
                 if CalculatorButton::new(
                     RichText::new("=").size(FONT_SIZE).color(Color32::WHITE),
                     Color32::from_rgb(66, 133, 244),
@@ -710,18 +1196,73 @@ impl Calculator {
                 {
                     self.solve();
                 }
+                self.operation_button(ui, "+", Add);
+            });
+        });
+    }
 
-                if CalculatorButton::new("+", FUNCTION_COLOR)
+    fn digit_buttons(&mut self, ui: &mut Ui, digits: &[&str]) {
+        for digit in digits {
+            let valid = digit.chars().all(|c| c.is_digit(self.radix.base()));
+            ui.add_enabled_ui(valid, |ui| {
+                if CalculatorButton::new(*digit, NUMBER_COLOR)
                     .ui(ui)
                     .clicked_or_drag_ended()
                 {
-                    self.try_continue_answer();
-                    self.equation.try_push(Add);
+                    self.equation
+                        .try_push(Number(format!("{}{digit}", self.radix.prefix())));
                     self.previous_answer_state = PreviousAnswerState::Hide;
                 }
             });
-        });
+        }
+    }
+
+    //an operation carries on from the last answer like the usual buttons do
+    fn operation_button(&mut self, ui: &mut Ui, text: &str, item: Item) {
+        if CalculatorButton::new(text, FUNCTION_COLOR)
+            .ui(ui)
+            .clicked_or_drag_ended()
+        {
+            self.try_continue_answer();
+            self.equation.try_push(item);
+            self.previous_answer_state = PreviousAnswerState::Hide;
+        }
     }
+
+    //the inverse toggle switches these to arsinh, arcosh and artanh like it does for sin
+    fn hyperbolic_button(&mut self, ui: &mut Ui, text: &str, item: Item, inverse_item: Item) {
+        if self.inverse {
+            if CalculatorButton::new(superscript(ui, text, "-1"), FUNCTION_COLOR)
+                .ui(ui)
+                .clicked_or_drag_ended()
+            {
+                self.equation.try_push(inverse_item);
+                self.inverse = false;
+                self.previous_answer_state = PreviousAnswerState::Hide;
+            }
+        } else {
+            if CalculatorButton::new(text, FUNCTION_COLOR)
+                .ui(ui)
+                .clicked_or_drag_ended()
+            {
+                self.equation.try_push(item);
+                self.previous_answer_state = PreviousAnswerState::Hide;
+            }
+        }
+    }
+
+    fn second_page_buttons(&mut self, ui: &mut Ui, items: &[(&str, Item)]) {
+        for (text, item) in items {
+            if CalculatorButton::new(*text, FUNCTION_COLOR)
+                .ui(ui)
+                .clicked_or_drag_ended()
+            {
+                self.equation.try_push(item.clone());
+                self.previous_answer_state = PreviousAnswerState::Hide;
+            }
+        }
+    }
+
     fn rad_deg_buttons(&mut self, ui: &mut Ui) {
         let disabled_color = Color32::from_rgb(135, 136, 140);
         let job = {
@@ -777,11 +1318,29 @@ impl Calculator {
 
     fn solve(&mut self) {
         if self.previous_answer_state != PreviousAnswerState::Show {
-            let answer = solver::solve(
+            let ans = self.history.last().map(|history| &history.1);
+            let variables = self.variables();
+            let answer = solve_answer(
                 &self.equation,
                 self.degrees,
-                self.history.last().map(|history| history.1).unwrap_or(0.0),
+                ans,
+                &variables,
+                &self.functions,
+                &self.rates,
+                self.precision(),
             );
+            //integers are already exact
+            self.exact_form = if self.mode == Mode::Programmer {
+                None
+            } else {
+                exact_form(
+                    &self.equation,
+                    self.degrees,
+                    ans,
+                    &variables,
+                    &self.functions,
+                )
+            };
 
             for _ in 0..self.equation.open_parentheses_count() {
                 self.equation.try_push(ClosingParenthesis);
@@ -789,22 +1348,20 @@ impl Calculator {
 
             let equation = std::mem::replace(&mut self.equation, Equation::new());
 
-            if let Some(answer) = answer {
-                self.previous_answer_state = PreviousAnswerState::Show;
-                if let Some(last) = self.history.last() {
-                    if last.0 != equation || last.0.contains_ans() {
-                        self.history.push((equation, answer));
-                    }
-                } else {
-                    self.history.push((equation, answer));
+            match answer {
+                Ok(answer) => {
+                    self.previous_answer_state = PreviousAnswerState::Show;
+                    push_history(&mut self.history, equation, answer);
+                    self.trim_history();
+                }
+                Err(error) => {
+                    self.previous_answer_state = PreviousAnswerState::Error(equation, error);
                 }
-            } else {
-                self.previous_answer_state = PreviousAnswerState::Error(equation);
             }
         }
         self.animation_time = Some(0.0);
     }
-    fn show_current(&self, ctx: &Context) {
+    fn show_current(&mut self, ctx: &Context) {
         let t = self.animation_time.unwrap_or(ANIMATION_DURATION) / ANIMATION_DURATION;
         let y_position = smoothstep(95.0, 43.0, t) + TITLE_BAR_HEIGHT / 2.0;
         Area::new("current answer")
@@ -819,26 +1376,150 @@ impl Calculator {
                     ui.add_space(22.0);
                     match &self.previous_answer_state {
                         PreviousAnswerState::Show => {
-                            ui.label(
-                                RichText::new(format_number(self.history.last().unwrap().1))
-                                    .size(EQUATION_SIZE),
-                            );
+                            let (equation, answer) = self.history.last().unwrap();
+                            let mut response = ui
+                                .add(
+                                    Label::new(
+                                        RichText::new(answer.format(self.answer_format()))
+                                            .size(EQUATION_SIZE),
+                                    )
+                                    .sense(Sense::click()),
+                                )
+                                .context_menu(|ui| copy_menu(ui, equation, Some(answer)));
+                            if answer.is_fraction() {
+                                response = response.on_hover_text(
+                                    "Click to switch between fraction, mixed number and decimal",
+                                );
+                                if response.clicked() {
+                                    self.fraction_display = self.fraction_display.next();
+                                }
+                            } else if answer.is_complex() {
+                                response = response.on_hover_text(
+                                    "Click to switch between rectangular and polar form",
+                                );
+                                if response.clicked() {
+                                    self.complex_display = self.complex_display.next();
+                                }
+                            }
+
+                            //√2/2 = 0.7071067811865476
+                            if let Some(exact) = &self.exact_form {
+                                if !answer.is_complex()
+                                    && *exact != answer.format(self.answer_format())
+                                {
+                                    ui.label(
+                                        RichText::new(format!("{exact} ="))
+                                            .size(EXACT_SIZE)
+                                            .color(PREVIOUS_COLOR),
+                                    );
+                                }
+                            }
                         }
-                        PreviousAnswerState::Hide => {
-                            ui.label(
-                                self.equation
-                                    .render(EQUATION_SIZE, ui.visuals().text_color())
-                                    .clone(),
-                            );
+                        PreviousAnswerState::Hide
+                        | PreviousAnswerState::PasteError(..)
+                        | PreviousAnswerState::Defined(..) => {
+                            ui.add(
+                                Label::new(
+                                    self.equation
+                                        .render(EQUATION_SIZE, ui.visuals().text_color())
+                                        .clone(),
+                                )
+                                .sense(Sense::click()),
+                            )
+                            .context_menu(|ui| copy_menu(ui, &self.equation, None));
                         }
-                        PreviousAnswerState::Error(equation) => {
-                            ui.label(RichText::new("Error").size(EQUATION_SIZE));
+                        PreviousAnswerState::Error(equation, error) => {
+                            ui.add(
+                                Label::new(RichText::new(error.to_string()).size(EQUATION_SIZE))
+                                    .sense(Sense::click()),
+                            )
+                            .context_menu(|ui| copy_menu(ui, equation, None));
                         }
                     }
                 });
             });
     }
 
+    //programmer mode shows the equation's value, or the answer, in every radix
+    fn show_radixes(&self, ctx: &Context) {
+        if self.mode != Mode::Programmer {
+            return;
+        }
+        let answer = match (&self.previous_answer_state, self.history.last()) {
+            (PreviousAnswerState::Show, Some((_, answer))) => Some(answer.clone()),
+            (PreviousAnswerState::Hide, _) if !self.equation.is_empty() => solve_answer(
+                &self.equation,
+                self.degrees,
+                self.history.last().map(|history| &history.1),
+                &self.variables(),
+                &self.functions,
+                &self.rates,
+                self.precision(),
+            )
+            .ok(),
+            _ => None,
+        };
+        let Some(Answer::Integer {
+            value,
+            word,
+            overflowed,
+            ..
+        }) = answer
+        else {
+            return;
+        };
+
+        Area::new("radixes")
+            .fixed_pos(pos2(60.0, 44.0 + TITLE_BAR_HEIGHT / 2.0))
+            .show(ctx, |ui| {
+                ui.spacing_mut().item_spacing.y = 0.0;
+                for radix in Radix::ALL {
+                    let text = format!("{} {}", radix.name(), format_integer(value, radix, word));
+                    ui.label(
+                        RichText::new(text)
+                            .size(11.0)
+                            .color(if radix == self.radix {
+                                ctx.style().visuals.text_color()
+                            } else {
+                                PREVIOUS_COLOR
+                            }),
+                    );
+                }
+                //wrapping around is easy to miss otherwise
+                if overflowed {
+                    ui.label(
+                        RichText::new(format!("Overflowed, wrapped to {} bits", word.bits))
+                            .size(11.0)
+                            .color(Color32::from_rgb(217, 48, 37)),
+                    );
+                }
+            });
+    }
+
+    //currency answers are only as good as the rates they were converted with
+    fn show_rates_updated(&self, ctx: &Context) {
+        let Some((equation, _)) = self.history.last() else {
+            return;
+        };
+        if self.previous_answer_state != PreviousAnswerState::Show || !equation.contains_currency()
+        {
+            return;
+        }
+        let Some(updated) = &self.rates.updated else {
+            return;
+        };
+
+        Area::new("rates updated")
+            .fixed_pos(pos2(60.0, 44.0 + TITLE_BAR_HEIGHT / 2.0))
+            .show(ctx, |ui| {
+                ui.label(
+                    RichText::new(format!("Rates from {updated}"))
+                        .size(11.0)
+                        .color(PREVIOUS_COLOR),
+                );
+            });
+    }
+
     fn show_previous(&self, ctx: &Context) {
         let t = self.animation_time.unwrap_or(ANIMATION_DURATION) / ANIMATION_DURATION;
         let size = smoothstep(EQUATION_SIZE, PREVIOUS_SIZE, t);
@@ -877,13 +1558,17 @@ impl Calculator {
                         PreviousAnswerState::Hide => {
                             if let Some(last) = self.history.last() {
                                 ui.label(
-                                    RichText::new(format!("Ans = {}", format_number(last.1)))
+                                    RichText::new(format!("Ans = {}", last.1))
                                         .size(size)
                                         .color(color),
                                 );
                             }
                         }
-                        PreviousAnswerState::Error(equation) => {
+                        PreviousAnswerState::PasteError(message)
+                        | PreviousAnswerState::Defined(message) => {
+                            ui.label(RichText::new(message).size(size).color(color));
+                        }
+                        PreviousAnswerState::Error(equation, _) => {
                             let mut render = equation.render(size, color);
                             render.append(
                                 " =",
@@ -935,13 +1620,9 @@ impl Calculator {
                         .show(ui, |ui| {
                             ui.set_max_width(450.0);
                             ui.set_min_height(180.0);
-                            if self.history.len() == 0 {
-                                ui.allocate_space(vec2(450.0, 30.0));
-                                ui.separator();
-                            } else {
-                                ui.allocate_space(vec2(0.0, 30.0));
-                                ui.separator();
-
+                            self.history_menu_header(ui);
+                            ui.separator();
+                            if !self.history.is_empty() {
                                 ScrollArea::vertical().max_width(450.0).show(ui, |ui| {
                                     ui.allocate_space(vec2(ui.available_width(), 14.0));
                                     for (equation, answer) in &self.history {
@@ -964,6 +1645,9 @@ impl Calculator {
                                                 Color32::from_rgb(210, 227, 252),
                                             ))
                                             .ui(ui)
+                                            .context_menu(|ui| {
+                                                copy_menu(ui, equation, Some(answer))
+                                            })
                                             .clicked()
                                             {
                                                 self.previous_answer_state =
@@ -981,7 +1665,7 @@ impl Calculator {
                                             ui.add_space(2.5);
 
                                             let response = CalculatorButton::new(
-                                                RichText::new(format_number(*answer))
+                                                RichText::new(answer.to_string())
                                                     .color(Color32::from_rgb(66, 133, 244)),
                                                 Color32::TRANSPARENT,
                                             )
@@ -994,13 +1678,15 @@ impl Calculator {
                                                 1.2,
                                                 Color32::from_rgb(210, 227, 252),
                                             ))
-                                            .ui(ui);
+                                            .ui(ui)
+                                            .context_menu(|ui| {
+                                                copy_menu(ui, equation, Some(answer))
+                                            });
                                             if response.clicked() {
                                                 self.previous_answer_state =
                                                     PreviousAnswerState::Hide;
                                                 self.show_history_menu = false;
-                                                self.equation = Equation::new();
-                                                self.equation.try_push(Rnd(format_number(*answer)));
+                                                self.equation = answer.to_equation();
                                             }
 
                                             if just_opened {
@@ -1022,12 +1708,396 @@ impl Calculator {
         }
     }
 
-    fn try_continue_answer(&mut self) {
-        if self.equation.is_empty() {
-            if self.previous_answer_state == PreviousAnswerState::Show {
-                if let Some((_, num)) = self.history.last() {
-                    self.equation.try_push(Rnd(format_number(*num)));
+    fn show_statistics(&mut self, ctx: &Context) {
+        let mut just_opened = false;
+
+        Area::new("statistics button")
+            .fixed_pos(pos2(19.0, 47.0 + TITLE_BAR_HEIGHT / 2.0))
+            .order(Order::Foreground)
+            .interactable(!self.show_statistics_menu && !self.show_history_menu)
+            .show(ctx, |ui| {
+                let response = Button::new(RichText::new("Σ").size(20.0).color(PREVIOUS_COLOR))
+                    .frame(false)
+                    .ui(ui);
+                if response.clicked_or_drag_ended() {
+                    self.show_statistics_menu = true;
+                    just_opened = true;
                 }
+            });
+        if self.show_statistics_menu {
+            Area::new("statistics")
+                .fixed_pos(pos2(7.5, 3.5 + TITLE_BAR_HEIGHT / 2.0))
+                .show(ctx, |ui| {
+                    egui::containers::Frame::none()
+                        .fill(Color32::WHITE)
+                        .shadow(Shadow {
+                            extrusion: 5.5,
+                            color: Color32::from_rgba_premultiplied(0, 0, 0, 40),
+                        })
+                        .rounding(ROUNDING)
+                        .inner_margin(Margin::from(10.0))
+                        .show(ui, |ui| {
+                            ui.set_max_width(450.0);
+                            ui.set_min_height(180.0);
+                            ui.label(RichText::new("Statistics").size(16.0));
+                            TextEdit::multiline(&mut self.statistics)
+                                .hint_text("Numbers separated by commas or spaces")
+                                .desired_width(450.0)
+                                .desired_rows(3)
+                                .ui(ui);
+                            ui.separator();
+
+                            match parse_values(&self.statistics) {
+                                Err(error) => {
+                                    ui.label(
+                                        RichText::new(format!(
+                                            "Couldn't read \"{}\"",
+                                            &self.statistics[error.span()]
+                                        ))
+                                        .color(PREVIOUS_COLOR),
+                                    );
+                                }
+                                Ok(values) => match Summary::new(&values) {
+                                    None => {
+                                        ui.label(RichText::new("No numbers").color(PREVIOUS_COLOR));
+                                    }
+                                    Some(summary) => self.statistics_results(ui, &summary),
+                                },
+                            }
+
+                            //typing in the list keeps it open
+                            let rect = ui.max_rect().expand(25.0);
+                            if !just_opened
+                                && !ctx.wants_keyboard_input()
+                                && !ui.rect_contains_pointer(rect)
+                            {
+                                self.show_statistics_menu = false;
+                            }
+                        });
+                });
+        }
+    }
+
+    //clicking a result puts its name in the equation, so it follows the list when it changes
+    fn statistics_results(&mut self, ui: &mut Ui, summary: &Summary) {
+        ui.horizontal_wrapped(|ui| {
+            for (name, value) in summary.entries() {
+                if CalculatorButton::new(
+                    RichText::new(format!("{name} = {}", format_number(value)))
+                        .size(16.0)
+                        .color(Color32::from_rgb(66, 133, 244)),
+                    Color32::TRANSPARENT,
+                )
+                .stroke(Stroke::new(1.2, FUNCTION_COLOR))
+                .min_size(vec2(0.0, 30.0))
+                .padding(vec2(9.0, 0.0))
+                .hover_fill(Color32::from_rgb(247, 248, 248))
+                .click_fill(Color32::from_rgb(232, 240, 254))
+                .ui(ui)
+                .clicked()
+                {
+                    self.equation.try_push(Variable(name.into()));
+                    self.previous_answer_state = PreviousAnswerState::Hide;
+                    self.show_statistics_menu = false;
+                }
+            }
+        });
+        if summary.modes.len() > 1 {
+            let modes = summary
+                .modes
+                .iter()
+                .map(|mode| format_number(*mode))
+                .collect::<Vec<_>>();
+            ui.label(RichText::new(format!("Modes: {}", modes.join(", "))).color(PREVIOUS_COLOR));
+        }
+    }
+
+    fn history_menu_header(&mut self, ui: &mut Ui) {
+        ui.allocate_ui_with_layout(
+            vec2(450.0, 30.0),
+            Layout::right_to_left(Align::Center),
+            |ui| {
+                ui.set_min_size(vec2(450.0, 30.0));
+                if Button::new(
+                    RichText::new("Clear history")
+                        .size(16.0)
+                        .color(Color32::from_rgb(66, 133, 244)),
+                )
+                .frame(false)
+                .ui(ui)
+                .clicked()
+                {
+                    self.history.clear();
+                    if self.previous_answer_state == PreviousAnswerState::Show {
+                        self.previous_answer_state = PreviousAnswerState::Hide;
+                    }
+                }
+
+                let response = DragValue::new(&mut self.history_limit)
+                    .clamp_range(1..=1000)
+                    .prefix("Keep ")
+                    .ui(ui);
+                if response.changed() {
+                    self.trim_history();
+                }
+            },
+        );
+    }
+
+    fn settings_menu(&mut self, ctx: &Context) {
+        Area::new("settings")
+            .fixed_pos(pos2(672.0, 1.0))
+            .show(ctx, |ui| {
+                ui.menu_button(RichText::new("⚙").font(FontId::proportional(20.0)), |ui| {
+                    ui.radio_value(&mut self.mode, Mode::Float, "Floating point");
+                    ui.radio_value(&mut self.mode, Mode::HighPrecision, "High precision");
+                    ui.add_enabled(
+                        self.mode == Mode::HighPrecision,
+                        DragValue::new(&mut self.digits)
+                            .clamp_range(1..=MAX_DIGITS)
+                            .suffix(" digits"),
+                    );
+                    ui.radio_value(&mut self.mode, Mode::Exact, "Exact fractions");
+                    ui.radio_value(&mut self.mode, Mode::Complex, "Complex numbers");
+                    ui.radio_value(&mut self.mode, Mode::Programmer, "Programmer");
+                    ui.add_enabled_ui(self.mode == Mode::Programmer, |ui| {
+                        ui.horizontal(|ui| {
+                            for bits in Word::SIZES {
+                                ui.radio_value(&mut self.word.bits, bits, bits.to_string());
+                            }
+                        });
+                        ui.checkbox(&mut self.word.signed, "Signed");
+                    });
+
+                    ui.separator();
+                    ui.label("Exchange rates");
+                    ui.horizontal(|ui| {
+                        TextEdit::singleline(&mut self.rates_path)
+                            .hint_text("rates.json or rates.csv")
+                            .desired_width(180.0)
+                            .ui(ui);
+                        if ui.button("Reload").clicked() {
+                            self.reload_rates();
+                        }
+                    });
+                    match (&self.rates_error, &self.rates.updated) {
+                        (Some(error), _) => {
+                            ui.label(
+                                RichText::new(error.to_string())
+                                    .size(14.0)
+                                    .color(Color32::from_rgb(217, 48, 37)),
+                            );
+                        }
+                        (None, Some(updated)) => {
+                            ui.label(
+                                RichText::new(format!(
+                                    "{} currencies, updated {updated}",
+                                    self.rates.rates.len()
+                                ))
+                                .size(14.0)
+                                .color(PREVIOUS_COLOR),
+                            );
+                        }
+                        (None, None) => {}
+                    }
+                });
+            });
+    }
+
+    //switches the buttons between the usual functions and hyperbolic and multi-argument ones,
+    //or the bitwise operators in programmer mode
+    fn page_toggle(&mut self, ctx: &Context) {
+        Area::new("second page")
+            .fixed_pos(pos2(604.0, 1.0))
+            .show(ctx, |ui| {
+                ui.toggle_value(
+                    &mut self.second_page,
+                    RichText::new("2nd").font(FontId::proportional(16.0)),
+                );
+            });
+    }
+
+    fn distributions_menu(&mut self, ctx: &Context) {
+        Area::new("distributions")
+            .fixed_pos(pos2(560.0, 1.0))
+            .show(ctx, |ui| {
+                ui.menu_button(
+                    RichText::new("P(X)").font(FontId::proportional(16.0)),
+                    |ui| {
+                        let previous = self.distribution;
+                        for distribution in Distribution::ALL {
+                            ui.radio_value(
+                                &mut self.distribution,
+                                distribution,
+                                distribution.name(),
+                            );
+                        }
+                        if self.distribution != previous {
+                            self.distribution_parameters = default_parameters(self.distribution);
+                        }
+                        ui.separator();
+
+                        ui.horizontal(|ui| {
+                            let density = if self.distribution.is_discrete() {
+                                "PMF"
+                            } else {
+                                "PDF"
+                            };
+                            ui.radio_value(&mut self.distribution_query, Query::Density, density);
+                            ui.radio_value(&mut self.distribution_query, Query::Cumulative, "CDF");
+                            ui.radio_value(
+                                &mut self.distribution_query,
+                                Query::Inverse,
+                                "Inverse CDF",
+                            );
+                        });
+                        let parameters = self.distribution.parameters();
+                        for ((name, _), value) in
+                            parameters.iter().zip(&mut self.distribution_parameters)
+                        {
+                            ui.horizontal(|ui| {
+                                ui.label(*name);
+                                DragValue::new(value).speed(0.1).ui(ui);
+                            });
+                        }
+                        ui.horizontal(|ui| {
+                            if self.distribution_query == Query::Inverse {
+                                ui.label("p");
+                                DragValue::new(&mut self.distribution_x)
+                                    .clamp_range(0.0..=1.0)
+                                    .speed(0.01)
+                                    .ui(ui);
+                            } else {
+                                ui.label("x");
+                                DragValue::new(&mut self.distribution_x).speed(0.1).ui(ui);
+                            }
+                        });
+                        ui.separator();
+
+                        //clicking the result puts it in the equation like a random number
+                        match self.distribution.evaluate(
+                            &self.distribution_parameters,
+                            self.distribution_query,
+                            self.distribution_x,
+                        ) {
+                            Ok(result) => {
+                                if ui.button(format!("= {}", format_number(result))).clicked() {
+                                    self.equation.try_push(Rnd(format_number(result)));
+                                    self.previous_answer_state = PreviousAnswerState::Hide;
+                                    ui.close_menu();
+                                }
+                            }
+                            Err(error) => {
+                                ui.label(error.to_string());
+                            }
+                        }
+                    },
+                );
+            });
+    }
+
+    fn variables_menu(&mut self, ctx: &Context) {
+        Area::new("variables")
+            .fixed_pos(pos2(640.0, 1.0))
+            .show(ctx, |ui| {
+                ui.menu_button(RichText::new("x=").font(FontId::proportional(18.0)), |ui| {
+                    let format = self.answer_format();
+                    let mut removed_variable = None;
+                    let mut removed_function = None;
+                    for (name, value) in &self.variables {
+                        ui.horizontal(|ui| {
+                            if ui
+                                .button(format!("{name} = {}", value.format(format)))
+                                .clicked()
+                            {
+                                self.equation.try_push(Variable(name.clone()));
+                                self.previous_answer_state = PreviousAnswerState::Hide;
+                                ui.close_menu();
+                            }
+                            if ui.small_button("🗑").clicked() {
+                                removed_variable = Some(name.clone());
+                            }
+                        });
+                    }
+                    for (name, function) in &self.functions {
+                        ui.horizontal(|ui| {
+                            if ui.button(function.to_text(name)).clicked() {
+                                self.equation.try_push(Call(name.clone()));
+                                self.previous_answer_state = PreviousAnswerState::Hide;
+                                ui.close_menu();
+                            }
+                            if ui.small_button("🗑").clicked() {
+                                removed_function = Some(name.clone());
+                            }
+                        });
+                    }
+                    if let Some(name) = removed_variable {
+                        self.variables.remove(&name);
+                    }
+                    if let Some(name) = removed_function {
+                        self.functions.remove(&name);
+                    }
+                    if self.variables.is_empty() && self.functions.is_empty() {
+                        ui.label(RichText::new("No variables").color(PREVIOUS_COLOR));
+                    }
+
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        TextEdit::singleline(&mut self.variable_name)
+                            .hint_text("Name")
+                            .desired_width(80.0)
+                            .ui(ui);
+                        let answer = self.history.last().map(|history| &history.1);
+                        let enabled =
+                            answer.is_some() && parser::is_variable_name(&self.variable_name);
+                        if ui.add_enabled(enabled, Button::new("Save Ans")).clicked() {
+                            let name = std::mem::take(&mut self.variable_name);
+                            self.variables.insert(name, answer.unwrap().clone());
+                        }
+                    });
+                });
+            });
+    }
+
+    //the statistics panel's results can be used like variables, saved variables come first
+    fn variables(&self) -> Variables {
+        let mut variables = parse_values(&self.statistics)
+            .ok()
+            .and_then(|values| Summary::new(&values))
+            .map(|summary| summary.variables())
+            .unwrap_or_default();
+        variables.extend(self.variables.clone());
+        variables
+    }
+
+    fn precision(&self) -> Precision {
+        match self.mode {
+            Mode::Float => Precision::Float,
+            Mode::HighPrecision => Precision::Decimal(self.digits),
+            Mode::Exact => Precision::Rational,
+            Mode::Complex => Precision::Complex,
+            Mode::Programmer => Precision::Integer(self.radix, self.word),
+        }
+    }
+
+    fn answer_format(&self) -> AnswerFormat {
+        AnswerFormat {
+            fraction: self.fraction_display,
+            complex: self.complex_display,
+            degrees: self.degrees,
+            radix: Some(self.radix),
+        }
+    }
+
+    fn trim_history(&mut self) {
+        let excess = self.history.len().saturating_sub(self.history_limit);
+        self.history.drain(..excess);
+    }
+
+    fn try_continue_answer(&mut self) {
+        if self.equation.is_empty() && self.previous_answer_state == PreviousAnswerState::Show {
+            if let Some((_, answer)) = self.history.last() {
+                self.equation = answer.to_equation();
             }
         }
     }
@@ -1058,21 +2128,57 @@ fn superscript(ui: &Ui, text: &str, superscript_text: &str) -> LayoutJob {
     job
 }
 
+fn default_parameters(distribution: Distribution) -> Vec<f64> {
+    distribution
+        .parameters()
+        .iter()
+        .map(|(_, value)| *value)
+        .collect()
+}
+
 fn smoothstep(start: f32, end: f32, t: f32) -> f32 {
     let t = t.clamp(0.0, 1.0);
     let t = -2.0 * t * t * t + 3.0 * t * t;
     end * t + start * (1.0 - t)
 }
 
-fn format_number(num: f64) -> String {
-    let integer_digits = num.abs().trunc().to_string().len();
-
-    if integer_digits < 13 {
-        num.to_string()
+fn format_latex_number(answer: &Answer) -> String {
+    let number = answer.to_string();
+    if let Some((mantissa, exponent)) = number.split_once("e+") {
+        format!(r"{mantissa} \times 10^{{{exponent}}}")
+    } else if let Some((mantissa, exponent)) = number.split_once("e-") {
+        format!(r"{mantissa} \times 10^{{-{exponent}}}")
     } else {
-        let e = integer_digits - 1;
-        let num = num / 10.0f64.powf((integer_digits - 1) as f64);
-        format!("{num:.7}e+{e}")
+        number
+    }
+}
+
+fn copy_menu(ui: &mut Ui, equation: &Equation, answer: Option<&Answer>) {
+    let mut copied = None;
+    if let Some(answer) = answer {
+        if ui.button("Copy result").clicked() {
+            copied = Some(answer.to_string());
+        }
+    }
+    if ui.button("Copy as text").clicked() {
+        copied = Some(match answer {
+            Some(answer) => format!("{} = {answer}", equation.to_text()),
+            None => equation.to_text(),
+        });
+    }
+    if ui.button("Copy as expression").clicked() {
+        copied = Some(equation.to_ascii());
+    }
+    if ui.button("Copy as LaTeX").clicked() {
+        copied = Some(match answer {
+            Some(answer) => format!("{} = {}", equation.to_latex(), format_latex_number(answer)),
+            None => equation.to_latex(),
+        });
+    }
+
+    if let Some(text) = copied {
+        ui.output_mut(|o| o.copied_text = text);
+        ui.close_menu();
     }
 }
 
@@ -1,9 +1,18 @@
-use egui::{text::LayoutJob, *};
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+
+use serde::{Deserialize, Serialize};
+
+use crate::currency::is_currency;
+use crate::parser::{split_assignment, tokenize, ParseError, Target};
+use crate::programmer::Radix;
+use crate::solver::{Memory, SolveError, Stored};
 use Item::*;
 
-const POWER_SCALE: f32 = 0.65;
+#[cfg(feature = "gui")]
+mod render;
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum Item {
     Number(String),
     Rnd(String),
@@ -30,48 +39,131 @@ pub enum Item {
     Atan,
     Nroot,
     Add,
+    I,
+    Variable(String),
+    //a user defined function, rendered as "f(" like the built in ones
+    Call(String),
+    //separates the arguments of a Call or a built in function that takes more than one
+    Comma,
+    Min,
+    Max,
+    Gcd,
+    Lcm,
+    Ncr,
+    Npr,
+    Round,
+    Mod,
+    Sinh,
+    Cosh,
+    Tanh,
+    Asinh,
+    Acosh,
+    Atanh,
+    Erf,
+    Erfc,
+    Gamma,
+    //ln|Γ(x)|
+    Lgamma,
+    Beta,
+    Digamma,
+    //the standard normal distribution's cdf and its inverse
+    NormCdf,
+    NormInv,
+    //bitwise operators for programmer mode, not is written like a function
+    And,
+    Or,
+    Xor,
+    Not,
+    LeftShift,
+    RightShift,
+    RotateLeft,
+    RotateRight,
+    //7 mod 3 and 7 div 3, written between their values unlike mod(7, 3)
+    Modulo,
+    IntegerDivide,
+    Floor,
+    Ceil,
+    Trunc,
+    Abs,
+    Sign,
+    //a unit like km that values are measured in, variables with the same name hide it
+    Unit(String),
+    //converts what's before it to the units after it, like 5 km in mi
+    In,
 }
 
 impl Item {
     pub fn is_opening_parenthesis(&self) -> bool {
         matches!(
             self,
-            OpeningParenthesis | Sin | Ln | Cos | Log | Tan | Sqrt | Asin | Acos | Atan | Nroot
+            OpeningParenthesis
+                | Sin
+                | Ln
+                | Cos
+                | Log
+                | Tan
+                | Sqrt
+                | Asin
+                | Acos
+                | Atan
+                | Call(..)
+                | Min
+                | Max
+                | Gcd
+                | Lcm
+                | Ncr
+                | Npr
+                | Round
+                | Mod
+                | Sinh
+                | Cosh
+                | Tanh
+                | Asinh
+                | Acosh
+                | Atanh
+                | Erf
+                | Erfc
+                | Gamma
+                | Lgamma
+                | Beta
+                | Digamma
+                | NormCdf
+                | NormInv
+                | Not
+                | Floor
+                | Ceil
+                | Trunc
+                | Abs
+                | Sign
         )
     }
 
+    //how many comma separated arguments an opening parenthesis takes
+    //log takes a base first and round takes a number of decimal places second
+    pub fn arguments(&self) -> RangeInclusive<usize> {
+        match self {
+            Call(..) | Min | Max => 1..=usize::MAX,
+            Gcd | Lcm => 2..=usize::MAX,
+            Ncr | Npr | Mod | Beta => 2..=2,
+            Log | Round => 1..=2,
+            _ => 1..=1,
+        }
+    }
+
     pub fn can_put_end_parenthesis_after(&self) -> bool {
         match self {
-            Number(num) => {
-                if num.len() == 1 {
-                    if num.chars().nth(0).unwrap() == '-' {
-                        false
-                    } else {
-                        true
-                    }
-                } else {
-                    true
-                }
-            }
-            Percent | Factorial | Pi | E | Ans | ClosingParenthesis | Rnd(..) => true,
+            Number(num) => num != "-",
+            Percent | Factorial | Pi | E | I | Ans | ClosingParenthesis | Rnd(..)
+            | Variable(..) | Unit(..) => true,
             _ => false,
         }
     }
 
     pub fn can_put_operation_after(&self) -> bool {
         match self {
-            Number(num) => {
-                if num.len() == 1 {
-                    if num.chars().nth(0).unwrap() == '-' {
-                        false
-                    } else {
-                        true
-                    }
-                } else {
-                    true
-                }
-            }
-            Percent | Factorial | Pi | E | Ans | ClosingParenthesis | Rnd(..) => true,
+            Number(num) => num != "-",
+            Percent | Factorial | Pi | E | I | Ans | ClosingParenthesis | Rnd(..)
+            | Variable(..) | Unit(..) => true,
             _ => false,
         }
     }
@@ -85,22 +177,55 @@ impl Item {
             match other {
                 _ if other.is_opening_parenthesis() => false,
                 ClosingParenthesis => false,
-                Power => match self {
-                    _ if self.is_opening_parenthesis() => true,
-                    _ => false,
-                },
-                Multiply | Divide => match self {
-                    _ if self.is_opening_parenthesis() => true,
-                    Power => true,
-                    _ => false,
-                },
-                _ => true,
+                Power | Nroot => false,
+                Multiply | Divide | Modulo | IntegerDivide => matches!(
+                    self,
+                    Power | Nroot | Multiply | Divide | Modulo | IntegerDivide
+                ),
+                _ => self.bitwise_precedence() >= other.bitwise_precedence(),
             }
         }
     }
+
+    //the bitwise operators bind looser than arithmetic, like in C
+    fn bitwise_precedence(&self) -> u8 {
+        match self {
+            Or => 0,
+            Xor => 1,
+            And => 2,
+            LeftShift | RightShift | RotateLeft | RotateRight => 3,
+            _ => 4,
+        }
+    }
+}
+
+//f(x, y) = x^2 + y, where the parameters are variables in the body
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Function {
+    pub parameters: Vec<String>,
+    pub body: Equation,
 }
 
-#[derive(Clone, PartialEq, Debug)]
+impl Function {
+    //written the way it's defined
+    pub fn to_text(&self, name: &str) -> String {
+        format!(
+            "{name}({}) = {}",
+            self.parameters.join(", "),
+            self.body.to_text()
+        )
+    }
+}
+
+pub type Functions = BTreeMap<String, Function>;
+
+#[derive(PartialEq)]
+enum LatexGroup {
+    Parenthesis(&'static str),
+    Exponent,
+}
+
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
 pub struct Equation {
     list: Vec<Item>,
 }
@@ -114,56 +239,186 @@ impl Equation {
         self.list.contains(&Ans)
     }
 
+    //variables and functions can change between solves, like Ans
+    pub fn contains_variables(&self) -> bool {
+        self.list
+            .iter()
+            .any(|item| matches!(item, Variable(..) | Call(..) | Unit(..)))
+    }
+
+    //units that aren't hidden by variables, including the ones in the functions it calls
+    pub fn units(&self, memory: &Memory) -> Vec<String> {
+        let mut units = vec![];
+        self.collect_units(memory, &[], &mut vec![], &mut units);
+        units
+    }
+
+    fn collect_units(
+        &self,
+        memory: &Memory,
+        parameters: &[String],
+        called: &mut Vec<String>,
+        units: &mut Vec<String>,
+    ) {
+        for item in &self.list {
+            //Ans and variables holding a quantity bring their unit along
+            let stored = match item {
+                Ans => Some(&memory.ans),
+                Variable(name) | Unit(name) if !parameters.contains(name) => {
+                    memory.variables.get(name)
+                }
+                _ => None,
+            };
+            for item in stored
+                .and_then(|stored| stored.unit.as_ref())
+                .iter()
+                .flat_map(|unit| &unit.list)
+            {
+                match item {
+                    Unit(name) if !units.contains(name) => units.push(name.clone()),
+                    _ => {}
+                }
+            }
+
+            match item {
+                Unit(name)
+                    if !memory.variables.contains_key(name)
+                        && !parameters.contains(name)
+                        && !units.contains(name) =>
+                {
+                    units.push(name.clone())
+                }
+                Call(name) if !called.contains(name) => {
+                    called.push(name.clone());
+                    if let Some(function) = memory.functions.get(name) {
+                        function
+                            .body
+                            .collect_units(memory, &function.parameters, called, units);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    //answers in a currency depend on when the rates are from
+    pub fn contains_currency(&self) -> bool {
+        self.list
+            .iter()
+            .any(|item| matches!(item, Unit(name) if is_currency(name)))
+    }
+
+    //whether it has to be solved keeping track of units
+    pub fn has_units(&self, memory: &Memory) -> bool {
+        self.list.contains(&In) || !self.units(memory).is_empty()
+    }
+
+    //splits 5 km in mi into 5 km, the index of in and mi
+    pub fn split_conversion(&self) -> Option<(Equation, usize, Equation)> {
+        let mut open = 0;
+        for (index, item) in self.list.iter().enumerate() {
+            match item {
+                _ if item.is_opening_parenthesis() => open += 1,
+                ClosingParenthesis => open -= 1,
+                In if open == 0 => {
+                    return Some((
+                        Equation {
+                            list: self.list[..index].to_vec(),
+                        },
+                        index,
+                        Equation {
+                            list: self.list[index + 1..].to_vec(),
+                        },
+                    ))
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
     pub fn is_empty(&self) -> bool {
         self.list.is_empty()
     }
 
-    pub fn clean(&self, ans: f64) -> Vec<Item> {
+    //each cleaned item is paired with the index of the item it came from
+    //variables that aren't in memory are left for the solver, they're function parameters
+    pub fn clean(&self, memory: &Memory) -> Result<Vec<(usize, Item)>, SolveError> {
         let mut cleaned = vec![];
 
-        for item in &self.list {
+        for (index, item) in self.list.iter().enumerate() {
             match item {
-                Rnd(num) => cleaned.push(Number(num.clone())),
-                Pi => cleaned.push(Number("3.141592653589793238462643383279502884197".into())),
-                E => cleaned.push(Number("2.7182818284590452353602874713527".into())),
-                Ans => cleaned.push(Number(ans.to_string())),
+                Rnd(num) => {
+                    push_implicit_multiply(&mut cleaned, index);
+                    cleaned.push((index, Number(num.clone())));
+                }
+                Pi | E | I => {
+                    push_implicit_multiply(&mut cleaned, index);
+                    cleaned.push((index, item.clone()));
+                }
+                Ans => {
+                    push_implicit_multiply(&mut cleaned, index);
+                    push_stored(&mut cleaned, index, &memory.ans);
+                }
+                //a number and its unit are one quantity, so 10 m / 4 s divides by 4 s
+                //units with a power like 16 m^2 are left to the power binding tighter
+                Unit(name) if !memory.variables.contains_key(name) => {
+                    let length = cleaned.len();
+                    push_implicit_multiply(&mut cleaned, index);
+                    if cleaned.len() == length
+                        || matches!(self.list.get(index + 1), Some(Power | Nroot))
+                    {
+                        cleaned.push((index, item.clone()));
+                        continue;
+                    }
+                    cleaned.pop();
+                    let operand = pop_operand(&mut cleaned).ok_or(SolveError::Syntax(index))?;
+                    cleaned.push((index, OpeningParenthesis));
+                    cleaned.extend(operand);
+                    cleaned.push((index, Multiply));
+                    cleaned.push((index, item.clone()));
+                    cleaned.push((index, ClosingParenthesis));
+                }
+                Variable(name) | Unit(name) => {
+                    push_implicit_multiply(&mut cleaned, index);
+                    match memory.variables.get(name) {
+                        Some(value) => push_stored(&mut cleaned, index, value),
+                        None => cleaned.push((index, item.clone())),
+                    }
+                }
                 Percent => {
-                    let last = cleaned.pop().unwrap();
-                    cleaned.push(OpeningParenthesis);
-                    cleaned.push(last);
-                    cleaned.push(Divide);
-                    cleaned.push(Number("100".into()));
-                    cleaned.push(ClosingParenthesis);
+                    let operand = pop_operand(&mut cleaned).ok_or(SolveError::Syntax(index))?;
+                    cleaned.push((index, OpeningParenthesis));
+                    cleaned.extend(operand);
+                    cleaned.push((index, Divide));
+                    cleaned.push((index, Number("100".into())));
+                    cleaned.push((index, ClosingParenthesis));
                 }
                 Factorial => {
-                    let last = cleaned.pop().unwrap();
-                    cleaned.push(OpeningParenthesis);
-                    cleaned.push(last);
-                    cleaned.push(Factorial);
-                    cleaned.push(ClosingParenthesis);
+                    let operand = pop_operand(&mut cleaned).ok_or(SolveError::Syntax(index))?;
+                    cleaned.push((index, OpeningParenthesis));
+                    cleaned.extend(operand);
+                    cleaned.push((index, Factorial));
+                    cleaned.push((index, ClosingParenthesis));
                 }
                 EXP => {
-                    cleaned.push(Multiply);
-                    cleaned.push(Number("10".into()));
-                    cleaned.push(Power);
+                    cleaned.push((index, Multiply));
+                    cleaned.push((index, Number("10".into())));
+                    cleaned.push((index, Power));
                 }
                 _ if item.is_opening_parenthesis() => {
-                    if let Some(last) = cleaned.last() {
-                        if last.can_put_operation_after() {
-                            cleaned.push(Multiply);
-                        }
-                    }
-                    cleaned.push(item.clone());
+                    push_implicit_multiply(&mut cleaned, index);
+                    cleaned.push((index, item.clone()));
                 }
-                _ => cleaned.push(item.clone()),
+                _ => cleaned.push((index, item.clone())),
             }
         }
 
         for _ in 0..self.open_parentheses_count() {
-            cleaned.push(ClosingParenthesis);
+            cleaned.push((self.list.len(), ClosingParenthesis));
         }
 
-        cleaned
+        Ok(cleaned)
     }
 
     pub fn backspace(&mut self) {
@@ -175,6 +430,14 @@ impl Equation {
                 num.pop();
             } else if num.len() > 1 {
                 num.pop();
+                //a prefix like 0x isn't a number on its own
+                let radix = Radix::of(num);
+                if radix != Radix::Decimal && num.ends_with(radix.prefix()) {
+                    num.truncate(num.len() - radix.prefix().len());
+                    if num.is_empty() {
+                        self.list.pop();
+                    }
+                }
             } else {
                 self.list.pop();
             }
@@ -183,10 +446,7 @@ impl Equation {
             Some(Power) | Some(Factorial) | Some(Percent)
         ) {
             if let Some(Number(num)) = self.list.iter_mut().nth_back(1) {
-                if num == "0." {
-                    self.list.pop();
-                    self.list.pop();
-                } else if num == "0" {
+                if num == "0." || num == "0" {
                     self.list.pop();
                     self.list.pop();
                 } else {
@@ -205,7 +465,13 @@ impl Equation {
             _ if item.is_opening_parenthesis() => {
                 if matches!(
                     self.list.last(),
-                    Some(ClosingParenthesis) | Some(Pi) | Some(E) | Some(Ans)
+                    Some(ClosingParenthesis)
+                        | Some(Pi)
+                        | Some(E)
+                        | Some(I)
+                        | Some(Ans)
+                        | Some(Variable(..))
+                        | Some(Unit(..))
                 ) {
                     self.list.push(Multiply);
                 }
@@ -226,6 +492,32 @@ impl Equation {
             }
             Number(num) => {
                 if let Some(Number(current_num)) = self.list.last_mut() {
+                    let radix = Radix::of(&num);
+                    let current_radix = Radix::of(current_num);
+                    //hexadecimal, octal and binary digits are pushed with their prefix like 0xA
+                    if radix != Radix::Decimal || current_radix != Radix::Decimal {
+                        let digits = num.strip_prefix(radix.prefix()).unwrap_or(&num);
+                        let sign = if current_num.starts_with('-') {
+                            "-"
+                        } else {
+                            ""
+                        };
+                        let current_digits = &current_num[sign.len()..];
+                        let zero = format!("{}0", current_radix.prefix());
+                        if current_digits.is_empty()
+                            || current_digits == zero && (radix == current_radix || zero == "0")
+                        {
+                            *current_num = format!("{sign}{num}");
+                        } else if radix == current_radix
+                            || radix == Radix::Decimal
+                                && digits.chars().all(|c| c.is_digit(current_radix.base()))
+                        {
+                            current_num.push_str(digits);
+                        } else {
+                            return false;
+                        }
+                        return true;
+                    }
                     if num == "." {
                         if current_num == "-" {
                             current_num.push_str("0.");
@@ -254,7 +546,10 @@ impl Equation {
                         Some(ClosingParenthesis)
                             | Some(Pi)
                             | Some(E)
+                            | Some(I)
                             | Some(Ans)
+                            | Some(Variable(..))
+                            | Some(Unit(..))
                             | Some(Rnd(..))
                             | Some(Percent)
                             | Some(Factorial)
@@ -269,12 +564,28 @@ impl Equation {
                     true
                 }
             }
-            Add | Multiply | Divide => {
+            Add | Multiply | Divide | Modulo | IntegerDivide | And | Or | Xor | LeftShift
+            | RightShift | RotateLeft | RotateRight | In => {
                 if let Some(last) = self.list.last_mut() {
                     if last.can_put_operation_after() {
                         self.list.push(item);
                         true
-                    } else if matches!(last, Add | Multiply | Divide | Subtract) {
+                    } else if matches!(
+                        last,
+                        Add | Multiply
+                            | Divide
+                            | Modulo
+                            | IntegerDivide
+                            | Subtract
+                            | And
+                            | Or
+                            | Xor
+                            | LeftShift
+                            | RightShift
+                            | RotateLeft
+                            | RotateRight
+                            | In
+                    ) {
                         *last = item;
                         true
                     } else {
@@ -325,7 +636,9 @@ impl Equation {
                                 true
                             }
                         }
-                        Percent | Divide | Multiply | Power | EXP => {
+                        Percent | Divide | Multiply | Modulo | IntegerDivide | Power | Nroot
+                        | EXP | Comma | And | Or | Xor | LeftShift | RightShift | RotateLeft
+                        | RotateRight | In => {
                             self.list.push(Number("-".into()));
                             true
                         }
@@ -362,13 +675,37 @@ impl Equation {
                     true
                 }
             }
-            Pi | E | Ans => {
+            Nroot => {
+                if let Some(last) = self.list.last() {
+                    if matches!(
+                        last,
+                        Number(..) | Pi | E | Ans | Rnd(..) | Variable(..) | Unit(..)
+                    ) && last.can_put_operation_after()
+                    {
+                        self.list.push(Nroot);
+                        true
+                    } else {
+                        false
+                    }
+                } else {
+                    false
+                }
+            }
+            //an answer carried on with its unit reads like a typed number, 3.1 mi
+            Unit(..) if matches!(self.list.last(), Some(Rnd(..))) => {
+                self.list.push(item);
+                true
+            }
+            Pi | E | I | Ans | Variable(..) | Unit(..) => {
                 if matches!(
                     self.list.last(),
                     Some(ClosingParenthesis)
                         | Some(Pi)
                         | Some(E)
+                        | Some(I)
                         | Some(Ans)
+                        | Some(Variable(..))
+                        | Some(Unit(..))
                         | Some(Rnd(..))
                         | Some(Percent)
                         | Some(Factorial)
@@ -389,11 +726,21 @@ impl Equation {
                 }
                 true
             }
+            Comma => {
+                let takes_more = self
+                    .innermost_parenthesis()
+                    .is_some_and(|parenthesis| *parenthesis.arguments().end() > 1);
+                match self.list.last() {
+                    Some(last) if takes_more && last.can_put_operation_after() => {
+                        self.list.push(Comma);
+                        true
+                    }
+                    _ => false,
+                }
+            }
             EXP => {
                 if let Some(Number(num)) = self.list.last().as_ref() {
-                    if num == "." {
-                        false
-                    } else if num == "-" {
+                    if num == "." || num == "-" || Radix::of(num) != Radix::Decimal {
                         false
                     } else {
                         self.list.push(EXP);
@@ -407,10 +754,54 @@ impl Equation {
         }
     }
 
+    //builds the equation the same way as typing the text in would
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut equation = Self::new();
+        equation.try_push_text(text)?;
+        Ok(equation)
+    }
+
+    //parses "x = 3.5" or "f(x) = x^2" into what's being named and its equation, other text like parse
+    pub fn parse_assignment(text: &str) -> Result<(Option<Target>, Self), ParseError> {
+        let Some((target, start)) = split_assignment(text)? else {
+            return Ok((None, Self::parse(text)?));
+        };
+        let equation = Self::parse(&text[start..]).map_err(|error| error.offset(start))?;
+        Ok((Some(target), equation))
+    }
+
+    //pushes every item in the text, leaving the equation unchanged if any of them can't be added
+    pub fn try_push_text(&mut self, text: &str) -> Result<(), ParseError> {
+        let mut equation = self.clone();
+        for (span, item) in tokenize(text)? {
+            if !equation.try_push(item) {
+                return Err(ParseError::UnexpectedSymbol(span));
+            }
+        }
+        *self = equation;
+        Ok(())
+    }
+
     pub fn clear(&mut self) {
         self.list.clear();
     }
 
+    //the last opening parenthesis that hasn't been closed
+    fn innermost_parenthesis(&self) -> Option<&Item> {
+        let mut closed = 0;
+        for item in self.list.iter().rev() {
+            if *item == ClosingParenthesis {
+                closed += 1;
+            } else if item.is_opening_parenthesis() {
+                if closed == 0 {
+                    return Some(item);
+                }
+                closed -= 1;
+            }
+        }
+        None
+    }
+
     pub fn open_parentheses_count(&self) -> usize {
         self.list
             .iter()
@@ -423,124 +814,407 @@ impl Equation {
                 .count()
     }
 
-    pub fn render(&self, size: f32, color: Color32) -> LayoutJob {
-        let mut job = LayoutJob::default();
-
-        if self.list.len() == 0 {
-            job.append(
-                "0",
-                0.0,
-                TextFormat {
-                    font_id: FontId::new(size, FontFamily::Name("roboto".into())),
-                    color,
-                    ..Default::default()
-                },
-            );
-            return job;
+    //the equation as it's displayed, with unclosed parentheses closed
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for (index, item) in self.list.iter().enumerate() {
+            let is_root_index = self.list.get(index + 1) == Some(&Nroot);
+            match item {
+                Number(num) | Rnd(num) if is_root_index => {
+                    text.extend(num.chars().map(superscript_char))
+                }
+                Number(num) | Rnd(num) => text.push_str(num),
+                Factorial => text.push('!'),
+                OpeningParenthesis => text.push('('),
+                ClosingParenthesis => text.push(')'),
+                Percent => text.push('%'),
+                Sin => text.push_str("sin("),
+                Ln => text.push_str("ln("),
+                Divide => text.push_str(" ÷ "),
+                Pi => text.push('π'),
+                Cos => text.push_str("cos("),
+                Log => text.push_str("log("),
+                Multiply => text.push_str(" × "),
+                E => text.push('e'),
+                Tan => text.push_str("tan("),
+                Sqrt => text.push_str("√("),
+                Subtract => text.push_str(" – "),
+                Ans => text.push_str("Ans"),
+                EXP => text.push('E'),
+                Power => text.push('^'),
+                Asin => text.push_str("arcsin("),
+                Acos => text.push_str("arccos("),
+                Atan => text.push_str("arctan("),
+                Nroot => text.push('√'),
+                Add => text.push_str(" + "),
+                I => text.push('i'),
+                Variable(name) => text.push_str(name),
+                Call(name) => {
+                    text.push_str(name);
+                    text.push('(');
+                }
+                Comma => text.push_str(", "),
+                Min => text.push_str("min("),
+                Max => text.push_str("max("),
+                Gcd => text.push_str("gcd("),
+                Lcm => text.push_str("lcm("),
+                Ncr => text.push_str("nCr("),
+                Npr => text.push_str("nPr("),
+                Round => text.push_str("round("),
+                Mod => text.push_str("mod("),
+                Sinh => text.push_str("sinh("),
+                Cosh => text.push_str("cosh("),
+                Tanh => text.push_str("tanh("),
+                Asinh => text.push_str("arsinh("),
+                Acosh => text.push_str("arcosh("),
+                Atanh => text.push_str("artanh("),
+                Erf => text.push_str("erf("),
+                Erfc => text.push_str("erfc("),
+                Gamma => text.push_str("gamma("),
+                Lgamma => text.push_str("lgamma("),
+                Beta => text.push_str("beta("),
+                Digamma => text.push_str("digamma("),
+                NormCdf => text.push_str("normcdf("),
+                NormInv => text.push_str("norminv("),
+                And => text.push_str(" AND "),
+                Or => text.push_str(" OR "),
+                Xor => text.push_str(" XOR "),
+                Not => text.push_str("NOT("),
+                LeftShift => text.push_str(" << "),
+                RightShift => text.push_str(" >> "),
+                RotateLeft => text.push_str(" ROL "),
+                RotateRight => text.push_str(" ROR "),
+                Modulo => text.push_str(" mod "),
+                IntegerDivide => text.push_str(" div "),
+                Floor => text.push_str("floor("),
+                Ceil => text.push_str("ceil("),
+                Trunc => text.push_str("trunc("),
+                Abs => text.push_str("abs("),
+                Sign => text.push_str("sign("),
+                Unit(name) => {
+                    if matches!(
+                        self.list.get(index.wrapping_sub(1)),
+                        Some(Number(..) | Rnd(..))
+                    ) {
+                        text.push(' ');
+                    }
+                    text.push_str(name);
+                }
+                In => text.push_str(" in "),
+            }
         }
+        for _ in 0..self.open_parentheses_count() {
+            text.push(')');
+        }
+        text
+    }
 
-        let mut default_layout = |text, power_level, font: &str| {
-            job.append(
-                text,
-                0.0,
-                TextFormat {
-                    font_id: FontId::new(
-                        size * POWER_SCALE.powf(power_level as f32),
-                        FontFamily::Name(font.into()),
-                    ),
-                    color,
-                    valign: Align::TOP,
-                    ..Default::default()
-                },
-            );
-        };
-
-        let mut level_open_parentheses_counts = vec![];
-        let mut parentheses_counts = vec![];
+    //plain ascii that Equation::parse turns back into the same equation
+    pub fn to_ascii(&self) -> String {
+        let mut text = String::new();
         for (index, item) in self.list.iter().enumerate() {
-            let power_level = parentheses_counts.len();
-            if power_level + 1 > level_open_parentheses_counts.len() {
-                level_open_parentheses_counts.push(0);
-            }
-            if item.is_opening_parenthesis() {
-                level_open_parentheses_counts[power_level] += 1;
-            }
-            if *item == ClosingParenthesis {
-                level_open_parentheses_counts[power_level] -= 1;
-            }
             match item {
-                Number(num) => default_layout(&num, power_level, "roboto"),
-                Factorial => default_layout("!", power_level, "roboto"),
-                OpeningParenthesis => default_layout("(", power_level, "roboto"),
-                ClosingParenthesis => default_layout(")", power_level, "roboto"),
-                Percent => default_layout("%", power_level, "roboto"),
-                Sin => default_layout("sin(", power_level, "roboto"),
-                Ln => default_layout("ln(", power_level, "roboto"),
-                Divide => default_layout(" ÷ ", power_level, "roboto"),
-                Pi => default_layout("π", power_level, "roboto"),
-                Cos => default_layout("cos(", power_level, "roboto"),
-                Log => default_layout("log(", power_level, "roboto"),
-                Multiply => default_layout(" × ", power_level, "roboto"),
-                E => default_layout("e", power_level, "roboto"),
-                Tan => default_layout("tan(", power_level, "roboto"),
-                Sqrt => default_layout("√(", power_level, "roboto"),
-                Subtract => default_layout(" – ", power_level, "roboto"),
-                EXP => default_layout("E", power_level, "roboto"),
-                Add => default_layout(" + ", power_level, "roboto"),
-                Ans => default_layout("Ans", power_level, "roboto"),
-                Asin => default_layout("arcsin(", power_level, "roboto"),
-                Acos => default_layout("arccos(", power_level, "roboto"),
-                Atan => default_layout("arctan(", power_level, "roboto"),
-                Rnd(num) => default_layout(&num, power_level, "roboto"),
-                Power => {
-                    parentheses_counts.push(0);
-                    if index == self.list.len() - 1 {
-                        default_layout("□", power_level + 1, "arial");
-                        if power_level + 2 > level_open_parentheses_counts.len() {
-                            level_open_parentheses_counts.push(0);
-                        }
+                Number(num) | Rnd(num) => text.push_str(num),
+                Factorial => text.push('!'),
+                OpeningParenthesis => text.push('('),
+                ClosingParenthesis => text.push(')'),
+                Percent => text.push('%'),
+                Sin => text.push_str("sin("),
+                Ln => text.push_str("ln("),
+                Divide => text.push('/'),
+                Pi => text.push_str("pi"),
+                Cos => text.push_str("cos("),
+                Log => text.push_str("log("),
+                Multiply => text.push('*'),
+                E => text.push('e'),
+                Tan => text.push_str("tan("),
+                Sqrt => text.push_str("sqrt("),
+                Subtract => text.push('-'),
+                Ans => text.push_str("Ans"),
+                EXP => text.push('E'),
+                Power => text.push('^'),
+                Asin => text.push_str("asin("),
+                Acos => text.push_str("acos("),
+                Atan => text.push_str("atan("),
+                Nroot => text.push_str(" root "),
+                Add => text.push('+'),
+                I => text.push('i'),
+                Variable(name) => text.push_str(name),
+                Call(name) => {
+                    text.push_str(name);
+                    text.push('(');
+                }
+                Comma => text.push(','),
+                Min => text.push_str("min("),
+                Max => text.push_str("max("),
+                Gcd => text.push_str("gcd("),
+                Lcm => text.push_str("lcm("),
+                Ncr => text.push_str("nCr("),
+                Npr => text.push_str("nPr("),
+                Round => text.push_str("round("),
+                Mod => text.push_str("mod("),
+                Sinh => text.push_str("sinh("),
+                Cosh => text.push_str("cosh("),
+                Tanh => text.push_str("tanh("),
+                Asinh => text.push_str("asinh("),
+                Acosh => text.push_str("acosh("),
+                Atanh => text.push_str("atanh("),
+                Erf => text.push_str("erf("),
+                Erfc => text.push_str("erfc("),
+                Gamma => text.push_str("gamma("),
+                Lgamma => text.push_str("lgamma("),
+                Beta => text.push_str("beta("),
+                Digamma => text.push_str("digamma("),
+                NormCdf => text.push_str("normcdf("),
+                NormInv => text.push_str("norminv("),
+                And => text.push_str(" and "),
+                Or => text.push_str(" or "),
+                Xor => text.push_str(" xor "),
+                Not => text.push_str("not("),
+                LeftShift => text.push_str("<<"),
+                RightShift => text.push_str(">>"),
+                RotateLeft => text.push_str(" rol "),
+                RotateRight => text.push_str(" ror "),
+                Modulo => text.push_str(" mod "),
+                IntegerDivide => text.push_str(" div "),
+                Floor => text.push_str("floor("),
+                Ceil => text.push_str("ceil("),
+                Trunc => text.push_str("trunc("),
+                Abs => text.push_str("abs("),
+                Sign => text.push_str("sign("),
+                Unit(name) => {
+                    if matches!(
+                        self.list.get(index.wrapping_sub(1)),
+                        Some(Number(..) | Rnd(..))
+                    ) {
+                        text.push(' ');
                     }
+                    text.push_str(name);
                 }
+                In => text.push_str(" in "),
+            }
+        }
+        text
+    }
 
-                _ => {}
+    //exponents and roots are grouped the same way render draws them
+    pub fn to_latex(&self) -> String {
+        let mut latex = String::new();
+        let mut groups = vec![];
+
+        for (index, item) in self.list.iter().enumerate() {
+            let next = self.list.get(index + 1);
+            if next == Some(&Nroot) {
+                latex.push_str(r"\sqrt[");
             }
-            if let Some(parentheses_count) = parentheses_counts.last_mut() {
-                if item.is_opening_parenthesis() {
-                    *parentheses_count += 1;
+            match item {
+                Number(num) | Rnd(num) => latex.push_str(num),
+                Factorial => latex.push('!'),
+                ClosingParenthesis => {
+                    if let Some(LatexGroup::Parenthesis(closer)) = groups.pop() {
+                        latex.push_str(closer);
+                    }
                 }
-                if *item == ClosingParenthesis {
-                    *parentheses_count -= 1;
+                Percent => latex.push_str(r"\%"),
+                Divide => latex.push_str(r" \div "),
+                Pi => latex.push_str(r"\pi "),
+                Multiply => latex.push_str(r" \times "),
+                E => latex.push('e'),
+                Subtract => latex.push_str(" - "),
+                Ans => latex.push_str(r"\mathrm{Ans}"),
+                EXP => latex.push_str(r"\mathrm{E}"),
+                Add => latex.push_str(" + "),
+                I => latex.push('i'),
+                And => latex.push_str(r" \mathbin{\mathrm{AND}} "),
+                Or => latex.push_str(r" \mathbin{\mathrm{OR}} "),
+                Xor => latex.push_str(r" \mathbin{\mathrm{XOR}} "),
+                LeftShift => latex.push_str(r" \ll "),
+                RightShift => latex.push_str(r" \gg "),
+                RotateLeft => latex.push_str(r" \mathbin{\mathrm{ROL}} "),
+                RotateRight => latex.push_str(r" \mathbin{\mathrm{ROR}} "),
+                Modulo => latex.push_str(r" \bmod "),
+                IntegerDivide => latex.push_str(r" \mathbin{\mathrm{div}} "),
+                Floor | Ceil | Abs => {
+                    let (opener, closer) = match item {
+                        Floor => (r"\left\lfloor ", r"\right\rfloor "),
+                        Ceil => (r"\left\lceil ", r"\right\rceil "),
+                        _ => (r"\left|", r"\right|"),
+                    };
+                    latex.push_str(opener);
+                    groups.push(LatexGroup::Parenthesis(closer));
                 }
-                if *parentheses_count == 0
-                    && item.can_put_operation_after()
-                    && self.list.get(index + 1) != Some(&Power)
-                {
-                    while parentheses_counts.last() == Some(&0) {
-                        parentheses_counts.pop();
+                Variable(name) if name.len() == 1 => latex.push_str(name),
+                Variable(name) => latex.push_str(&format!(r"\mathrm{{{name}}}")),
+                Unit(name) => latex.push_str(&format!(r"\,\mathrm{{{name}}}")),
+                In => latex.push_str(r" \mathbin{\mathrm{in}} "),
+                Comma => latex.push_str(", "),
+                Power => {
+                    latex.push_str("^{");
+                    groups.push(LatexGroup::Exponent);
+                }
+                Nroot => {
+                    latex.push('{');
+                    groups.push(LatexGroup::Exponent);
+                }
+                Sqrt => {
+                    latex.push_str(r"\sqrt{");
+                    groups.push(LatexGroup::Parenthesis("}"));
+                }
+                Call(name) => {
+                    if name.len() == 1 {
+                        latex.push_str(name);
+                    } else {
+                        latex.push_str(&format!(r"\operatorname{{{name}}}"));
                     }
+                    latex.push_str(r"\left(");
+                    groups.push(LatexGroup::Parenthesis(r"\right)"));
+                }
+                Sin | Ln | Cos | Log | Tan | Asin | Acos | Atan | OpeningParenthesis | Min
+                | Max | Gcd | Lcm | Ncr | Npr | Round | Mod | Sinh | Cosh | Tanh | Asinh
+                | Acosh | Atanh | Erf | Erfc | Gamma | Lgamma | Beta | Digamma | NormCdf
+                | NormInv | Not | Trunc | Sign => {
+                    latex.push_str(match item {
+                        Min => r"\min\left(",
+                        Max => r"\max\left(",
+                        Gcd => r"\gcd\left(",
+                        Lcm => r"\operatorname{lcm}\left(",
+                        Ncr => r"\operatorname{nCr}\left(",
+                        Npr => r"\operatorname{nPr}\left(",
+                        Round => r"\operatorname{round}\left(",
+                        Mod => r"\operatorname{mod}\left(",
+                        Sin => r"\sin\left(",
+                        Ln => r"\ln\left(",
+                        Cos => r"\cos\left(",
+                        Log => r"\log\left(",
+                        Tan => r"\tan\left(",
+                        Asin => r"\arcsin\left(",
+                        Acos => r"\arccos\left(",
+                        Atan => r"\arctan\left(",
+                        Sinh => r"\sinh\left(",
+                        Cosh => r"\cosh\left(",
+                        Tanh => r"\tanh\left(",
+                        Asinh => r"\operatorname{arsinh}\left(",
+                        Acosh => r"\operatorname{arcosh}\left(",
+                        Atanh => r"\operatorname{artanh}\left(",
+                        Erf => r"\operatorname{erf}\left(",
+                        Erfc => r"\operatorname{erfc}\left(",
+                        Gamma => r"\Gamma\left(",
+                        Lgamma => r"\ln\Gamma\left(",
+                        Beta => r"\mathrm{B}\left(",
+                        Digamma => r"\psi\left(",
+                        NormCdf => r"\Phi\left(",
+                        NormInv => r"\Phi^{-1}\left(",
+                        Not => r"\operatorname{NOT}\left(",
+                        Trunc => r"\operatorname{trunc}\left(",
+                        Sign => r"\operatorname{sgn}\left(",
+                        _ => r"\left(",
+                    });
+                    groups.push(LatexGroup::Parenthesis(r"\right)"));
                 }
             }
-        }
+            if next == Some(&Nroot) {
+                latex.push(']');
+            }
 
-        for (level, open_parens) in level_open_parentheses_counts.iter().rev().enumerate() {
-            let level =
-                (level_open_parentheses_counts.len() as i32 - level as i32 - 1).clamp(0, i32::MAX);
-            for _ in 0..*open_parens {
-                job.append(
-                    ")",
-                    0.0,
-                    TextFormat {
-                        font_id: FontId::new(
-                            size * POWER_SCALE.powf(level as f32),
-                            FontFamily::Name("roboto".into()),
-                        ),
-                        color: Color32::from_rgb(204, 204, 204),
-                        valign: Align::TOP,
-                        ..Default::default()
-                    },
-                );
+            if item.can_put_operation_after() && next != Some(&Power) && next != Some(&Nroot) {
+                while groups.last() == Some(&LatexGroup::Exponent) {
+                    groups.pop();
+                    latex.push('}');
+                }
             }
         }
-        job
+
+        while let Some(group) = groups.pop() {
+            latex.push_str(match group {
+                LatexGroup::Parenthesis(closer) => closer,
+                LatexGroup::Exponent => "}",
+            });
+        }
+        latex.trim_end().into()
+    }
+}
+
+//a quantity goes in parentheses with its unit, so Ans^2 squares the unit too
+fn push_stored(cleaned: &mut Vec<(usize, Item)>, index: usize, stored: &Stored) {
+    let Some(unit) = &stored.unit else {
+        cleaned.push((index, Number(stored.number.clone())));
+        return;
+    };
+    cleaned.push((index, OpeningParenthesis));
+    cleaned.push((index, Number(stored.number.clone())));
+    cleaned.push((index, Multiply));
+    cleaned.extend(unit.list.iter().map(|item| (index, item.clone())));
+    cleaned.push((index, ClosingParenthesis));
+}
+
+//the last value of the cleaned items, a whole parenthesis or function call like sin(30)
+fn pop_operand(cleaned: &mut Vec<(usize, Item)>) -> Option<Vec<(usize, Item)>> {
+    let mut closed = 0;
+    for start in (0..cleaned.len()).rev() {
+        let item = &cleaned[start].1;
+        if *item == ClosingParenthesis {
+            closed += 1;
+        } else if item.is_opening_parenthesis() {
+            closed -= 1;
+        }
+        if closed == 0 {
+            return Some(cleaned.split_off(start));
+        } else if closed < 0 {
+            return None;
+        }
+    }
+    None
+}
+
+//skips repeating the last entry unless it depends on Ans
+//"2π" and "-π" are multiplications
+fn push_implicit_multiply(cleaned: &mut Vec<(usize, Item)>, index: usize) {
+    match cleaned.last_mut() {
+        Some((_, Number(num))) if num == "-" => {
+            *num = "-1".into();
+            cleaned.push((index, Multiply));
+        }
+        Some((_, last)) if last.can_put_operation_after() => cleaned.push((index, Multiply)),
+        _ => {}
+    }
+}
+
+pub fn push_history<T>(history: &mut Vec<(Equation, T)>, equation: Equation, answer: T) {
+    if let Some(last) = history.last() {
+        if last.0 != equation || last.0.contains_ans() || last.0.contains_variables() {
+            history.push((equation, answer));
+        }
+    } else {
+        history.push((equation, answer));
+    }
+}
+
+pub fn format_number(num: f64) -> String {
+    let integer_digits = num.abs().trunc().to_string().len();
+
+    if integer_digits < 13 {
+        num.to_string()
+    } else {
+        let e = integer_digits - 1;
+        let num = num / 10.0f64.powf((integer_digits - 1) as f64);
+        format!("{num:.7}e+{e}")
+    }
+}
+
+pub(crate) fn superscript_char(c: char) -> char {
+    match c {
+        '0' => '⁰',
+        '1' => '¹',
+        '2' => '²',
+        '3' => '³',
+        '4' => '⁴',
+        '5' => '⁵',
+        '6' => '⁶',
+        '7' => '⁷',
+        '8' => '⁸',
+        '9' => '⁹',
+        '-' => '⁻',
+        '.' => '·',
+        _ => c,
     }
 }
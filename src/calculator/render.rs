@@ -0,0 +1,192 @@
+use egui::{text::LayoutJob, *};
+
+use super::{Equation, Item::*};
+
+const POWER_SCALE: f32 = 0.65;
+
+impl Equation {
+    pub fn render(&self, size: f32, color: Color32) -> LayoutJob {
+        let mut job = LayoutJob::default();
+
+        if self.list.is_empty() {
+            job.append(
+                "0",
+                0.0,
+                TextFormat {
+                    font_id: FontId::new(size, FontFamily::Name("roboto".into())),
+                    color,
+                    ..Default::default()
+                },
+            );
+            return job;
+        }
+
+        let mut default_layout = |text, power_level, font: &str| {
+            job.append(
+                text,
+                0.0,
+                TextFormat {
+                    font_id: FontId::new(
+                        size * POWER_SCALE.powf(power_level as f32),
+                        FontFamily::Name(font.into()),
+                    ),
+                    color,
+                    valign: Align::TOP,
+                    ..Default::default()
+                },
+            );
+        };
+
+        let mut level_open_parentheses_counts = vec![];
+        let mut parentheses_counts = vec![];
+        for (index, item) in self.list.iter().enumerate() {
+            let power_level = parentheses_counts.len();
+            if power_level + 1 > level_open_parentheses_counts.len() {
+                level_open_parentheses_counts.push(0);
+            }
+            if item.is_opening_parenthesis() {
+                level_open_parentheses_counts[power_level] += 1;
+            }
+            if *item == ClosingParenthesis {
+                level_open_parentheses_counts[power_level] -= 1;
+            }
+            match item {
+                Number(num) | Rnd(num) if self.list.get(index + 1) == Some(&Nroot) => {
+                    default_layout(num, power_level + 1, "roboto")
+                }
+                Pi if self.list.get(index + 1) == Some(&Nroot) => {
+                    default_layout("π", power_level + 1, "roboto")
+                }
+                E if self.list.get(index + 1) == Some(&Nroot) => {
+                    default_layout("e", power_level + 1, "roboto")
+                }
+                Ans if self.list.get(index + 1) == Some(&Nroot) => {
+                    default_layout("Ans", power_level + 1, "roboto")
+                }
+                Variable(name) if self.list.get(index + 1) == Some(&Nroot) => {
+                    default_layout(name, power_level + 1, "roboto")
+                }
+                Number(num) => default_layout(num, power_level, "roboto"),
+                Factorial => default_layout("!", power_level, "roboto"),
+                OpeningParenthesis => default_layout("(", power_level, "roboto"),
+                ClosingParenthesis => default_layout(")", power_level, "roboto"),
+                Percent => default_layout("%", power_level, "roboto"),
+                Sin => default_layout("sin(", power_level, "roboto"),
+                Ln => default_layout("ln(", power_level, "roboto"),
+                Divide => default_layout(" ÷ ", power_level, "roboto"),
+                Pi => default_layout("π", power_level, "roboto"),
+                Cos => default_layout("cos(", power_level, "roboto"),
+                Log => default_layout("log(", power_level, "roboto"),
+                Multiply => default_layout(" × ", power_level, "roboto"),
+                E => default_layout("e", power_level, "roboto"),
+                Tan => default_layout("tan(", power_level, "roboto"),
+                Sqrt => default_layout("√(", power_level, "roboto"),
+                Subtract => default_layout(" – ", power_level, "roboto"),
+                EXP => default_layout("E", power_level, "roboto"),
+                Add => default_layout(" + ", power_level, "roboto"),
+                Ans => default_layout("Ans", power_level, "roboto"),
+                Asin => default_layout("arcsin(", power_level, "roboto"),
+                Acos => default_layout("arccos(", power_level, "roboto"),
+                Atan => default_layout("arctan(", power_level, "roboto"),
+                Rnd(num) => default_layout(num, power_level, "roboto"),
+                Nroot => default_layout("√", power_level, "roboto"),
+                I => default_layout("i", power_level, "roboto"),
+                Variable(name) => default_layout(name, power_level, "roboto"),
+                Call(name) => {
+                    default_layout(name, power_level, "roboto");
+                    default_layout("(", power_level, "roboto");
+                }
+                Comma => default_layout(", ", power_level, "roboto"),
+                Min => default_layout("min(", power_level, "roboto"),
+                Max => default_layout("max(", power_level, "roboto"),
+                Gcd => default_layout("gcd(", power_level, "roboto"),
+                Lcm => default_layout("lcm(", power_level, "roboto"),
+                Ncr => default_layout("nCr(", power_level, "roboto"),
+                Npr => default_layout("nPr(", power_level, "roboto"),
+                Round => default_layout("round(", power_level, "roboto"),
+                Mod => default_layout("mod(", power_level, "roboto"),
+                Sinh => default_layout("sinh(", power_level, "roboto"),
+                Cosh => default_layout("cosh(", power_level, "roboto"),
+                Tanh => default_layout("tanh(", power_level, "roboto"),
+                Asinh => default_layout("arsinh(", power_level, "roboto"),
+                Acosh => default_layout("arcosh(", power_level, "roboto"),
+                Atanh => default_layout("artanh(", power_level, "roboto"),
+                Erf => default_layout("erf(", power_level, "roboto"),
+                Erfc => default_layout("erfc(", power_level, "roboto"),
+                Gamma => default_layout("gamma(", power_level, "roboto"),
+                Lgamma => default_layout("lgamma(", power_level, "roboto"),
+                Beta => default_layout("beta(", power_level, "roboto"),
+                Digamma => default_layout("digamma(", power_level, "roboto"),
+                NormCdf => default_layout("normcdf(", power_level, "roboto"),
+                NormInv => default_layout("norminv(", power_level, "roboto"),
+                And => default_layout(" AND ", power_level, "roboto"),
+                Or => default_layout(" OR ", power_level, "roboto"),
+                Xor => default_layout(" XOR ", power_level, "roboto"),
+                Not => default_layout("NOT(", power_level, "roboto"),
+                LeftShift => default_layout(" << ", power_level, "roboto"),
+                RightShift => default_layout(" >> ", power_level, "roboto"),
+                RotateLeft => default_layout(" ROL ", power_level, "roboto"),
+                RotateRight => default_layout(" ROR ", power_level, "roboto"),
+                Modulo => default_layout(" mod ", power_level, "roboto"),
+                IntegerDivide => default_layout(" div ", power_level, "roboto"),
+                Floor => default_layout("floor(", power_level, "roboto"),
+                Ceil => default_layout("ceil(", power_level, "roboto"),
+                Trunc => default_layout("trunc(", power_level, "roboto"),
+                Abs => default_layout("abs(", power_level, "roboto"),
+                Sign => default_layout("sign(", power_level, "roboto"),
+                Unit(name) if index > 0 && matches!(self.list[index - 1], Number(..) | Rnd(..)) => {
+                    default_layout(" ", power_level, "roboto");
+                    default_layout(name, power_level, "roboto");
+                }
+                Unit(name) => default_layout(name, power_level, "roboto"),
+                In => default_layout(" in ", power_level, "roboto"),
+                Power => {
+                    parentheses_counts.push(0);
+                    if index == self.list.len() - 1 {
+                        default_layout("□", power_level + 1, "arial");
+                        if power_level + 2 > level_open_parentheses_counts.len() {
+                            level_open_parentheses_counts.push(0);
+                        }
+                    }
+                }
+            }
+            if let Some(parentheses_count) = parentheses_counts.last_mut() {
+                if item.is_opening_parenthesis() {
+                    *parentheses_count += 1;
+                }
+                if *item == ClosingParenthesis {
+                    *parentheses_count -= 1;
+                }
+                if *parentheses_count == 0
+                    && item.can_put_operation_after()
+                    && self.list.get(index + 1) != Some(&Power)
+                {
+                    while parentheses_counts.last() == Some(&0) {
+                        parentheses_counts.pop();
+                    }
+                }
+            }
+        }
+
+        for (level, open_parens) in level_open_parentheses_counts.iter().rev().enumerate() {
+            let level =
+                (level_open_parentheses_counts.len() as i32 - level as i32 - 1).clamp(0, i32::MAX);
+            for _ in 0..*open_parens {
+                job.append(
+                    ")",
+                    0.0,
+                    TextFormat {
+                        font_id: FontId::new(
+                            size * POWER_SCALE.powf(level as f32),
+                            FontFamily::Name("roboto".into()),
+                        ),
+                        color: Color32::from_rgb(204, 204, 204),
+                        valign: Align::TOP,
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+        job
+    }
+}
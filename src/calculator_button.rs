@@ -44,7 +44,7 @@ impl CalculatorButton {
 
     pub fn stroke(self, stroke: Stroke) -> Self {
         Self {
-            click_stroke: stroke.clone(),
+            click_stroke: stroke,
             stroke,
             ..self
         }
@@ -0,0 +1,234 @@
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+
+use calculator::{
+    load_rates, push_history, solve_answer, Answer, AnswerFormat, ComplexDisplay, Equation,
+    Function, Functions, ParseError, Precision, Rates, Target, Variables, MAX_DIGITS,
+};
+
+use crate::repl;
+
+pub const EXIT_SOLVE_ERROR: i32 = 1;
+pub const EXIT_PARSE_ERROR: i32 = 2;
+pub const EXIT_USAGE: i32 = 64;
+
+const USAGE: &str = "\
+Usage: calculator [OPTIONS]
+
+Runs the calculator window when no options are given.
+
+Options:
+  -e, --eval <EXPRESSION>  Evaluate an expression and print the result
+  -s, --stdin              Evaluate every line read from standard input
+      --repl               Start an interactive session
+  -d, --degrees            Use degrees for trigonometric functions (default)
+  -r, --radians            Use radians for trigonometric functions
+  -p, --precision <DIGITS> Calculate with decimals accurate to DIGITS significant digits
+  -x, --exact              Keep results as exact fractions when possible
+  -c, --complex            Calculate with complex numbers, i is the imaginary unit
+      --polar              Print complex results in polar form
+      --rates <FILE>       Read exchange rates for currencies like USD from a JSON or CSV file
+  -h, --help               Print this message
+
+Ans refers to the previous result, the same as in the window.
+Lines like x = 3.5 save a result as a variable that later expressions can use,
+and lines like f(x, y) = x^2 + y define a function that they can call.
+Numbers can have units, and in or to converts them, like 5 km + 300 m in mi.";
+
+//returns the exit code
+pub fn run(args: &[String]) -> i32 {
+    let mut degrees = true;
+    let mut expressions = vec![];
+    let mut read_stdin = false;
+    let mut start_repl = false;
+    let mut precision = Precision::Float;
+    let mut complex_display = ComplexDisplay::Rectangular;
+    let mut rates_path = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-e" | "--eval" => match args.next() {
+                Some(expression) => expressions.push(expression.clone()),
+                None => {
+                    eprintln!("error: {arg} needs an expression\n\n{USAGE}");
+                    return EXIT_USAGE;
+                }
+            },
+            "-s" | "--stdin" | "-" => read_stdin = true,
+            "--repl" => start_repl = true,
+            "-d" | "--degrees" => degrees = true,
+            "-r" | "--radians" => degrees = false,
+            "-x" | "--exact" => precision = Precision::Rational,
+            "-c" | "--complex" => precision = Precision::Complex,
+            "--polar" => complex_display = ComplexDisplay::Polar,
+            "--rates" => match args.next() {
+                Some(path) => rates_path = Some(PathBuf::from(path)),
+                None => {
+                    eprintln!("error: {arg} needs a file\n\n{USAGE}");
+                    return EXIT_USAGE;
+                }
+            },
+            "-p" | "--precision" => {
+                match args.next().map(|digits| digits.parse()) {
+                    Some(Ok(digits)) if (1..=MAX_DIGITS).contains(&digits) => {
+                        precision = Precision::Decimal(digits)
+                    }
+                    _ => {
+                        eprintln!("error: {arg} needs a number of digits from 1 to {MAX_DIGITS}\n\n{USAGE}");
+                        return EXIT_USAGE;
+                    }
+                }
+            }
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return 0;
+            }
+            _ => {
+                eprintln!("error: unknown option {arg}\n\n{USAGE}");
+                return EXIT_USAGE;
+            }
+        }
+    }
+
+    let rates = match rates_path.as_deref().map(read_rates) {
+        Some(Ok(rates)) => rates,
+        Some(Err(code)) => return code,
+        None => Rates::default(),
+    };
+
+    if start_repl {
+        return repl::run(degrees, precision, complex_display, rates_path, rates);
+    }
+
+    if expressions.is_empty() && !read_stdin {
+        eprintln!("error: nothing to evaluate\n\n{USAGE}");
+        return EXIT_USAGE;
+    }
+
+    let mut session = Session {
+        rates,
+        ..Default::default()
+    };
+    let mut exit_code = 0;
+
+    for expression in expressions {
+        let result = evaluate(
+            &expression,
+            degrees,
+            precision,
+            complex_display,
+            &mut session,
+        );
+        if let Err(code) = result {
+            exit_code = code;
+        }
+    }
+
+    if read_stdin {
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            let result = evaluate(&line, degrees, precision, complex_display, &mut session);
+            if let Err(code) = result {
+                exit_code = code;
+            }
+        }
+    }
+
+    exit_code
+}
+
+//what later expressions can refer to
+#[derive(Default)]
+pub struct Session {
+    pub ans: Option<Answer>,
+    //the REPL lists these with :history
+    pub history: Vec<(Equation, Answer)>,
+    pub variables: Variables,
+    pub functions: Functions,
+    pub rates: Rates,
+}
+
+//prints the result or the error, updating ans, variables and functions the same way the window does
+pub fn evaluate(
+    text: &str,
+    degrees: bool,
+    precision: Precision,
+    complex_display: ComplexDisplay,
+    session: &mut Session,
+) -> Result<(), i32> {
+    let (target, equation) = match Equation::parse_assignment(text) {
+        Ok(assignment) => assignment,
+        Err(error) => {
+            report_parse_error(text, &error);
+            return Err(EXIT_PARSE_ERROR);
+        }
+    };
+
+    if let Some(Target::Function(name, parameters)) = target {
+        let function = Function {
+            parameters,
+            body: equation,
+        };
+        println!("{}", function.to_text(&name));
+        session.functions.insert(name, function);
+        return Ok(());
+    }
+
+    let answer = solve_answer(
+        &equation,
+        degrees,
+        session.ans.as_ref(),
+        &session.variables,
+        &session.functions,
+        &session.rates,
+        precision,
+    );
+    match answer {
+        Ok(answer) => {
+            let format = AnswerFormat {
+                complex: complex_display,
+                degrees,
+                ..Default::default()
+            };
+            match target {
+                Some(Target::Variable(name)) => {
+                    println!("{name} = {}", answer.format(format));
+                    session.variables.insert(name, answer.clone());
+                }
+                _ => println!("{}", answer.format(format)),
+            }
+            push_history(&mut session.history, equation, answer.clone());
+            session.ans = Some(answer);
+            Ok(())
+        }
+        Err(error) => {
+            eprintln!("error: {error}");
+            Err(EXIT_SOLVE_ERROR)
+        }
+    }
+}
+
+pub fn read_rates(path: &Path) -> Result<Rates, i32> {
+    load_rates(path).map_err(|error| {
+        eprintln!("error: {error}");
+        EXIT_USAGE
+    })
+}
+
+//points at the part of the text that couldn't be parsed
+pub fn report_parse_error(text: &str, error: &ParseError) {
+    let span = error.span();
+    let offset = text[..span.start].chars().count();
+    let width = text[span].chars().count().max(1);
+    eprintln!(
+        "error: {error}\n  {text}\n  {}{}",
+        " ".repeat(offset),
+        "^".repeat(width)
+    );
+}
@@ -0,0 +1,258 @@
+use std::f64::consts::PI;
+
+use num_complex::Complex64;
+use serde::{Deserialize, Serialize};
+
+use crate::calculator::{format_number, Item};
+use crate::solver::{Backend, Fault, Float, SolveError};
+use Item::*;
+
+//parts smaller than this compared to the other part are rounding errors, so e^(iπ) is -1
+const TIDY_RATIO: f64 = 1e-15;
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum ComplexDisplay {
+    #[default]
+    Rectangular,
+    Polar,
+}
+
+impl ComplexDisplay {
+    pub fn next(self) -> Self {
+        match self {
+            ComplexDisplay::Rectangular => ComplexDisplay::Polar,
+            ComplexDisplay::Polar => ComplexDisplay::Rectangular,
+        }
+    }
+}
+
+//solves with complex floats, so √(-4) is 2i instead of a domain error
+pub struct Complex;
+
+impl Complex {
+    fn power(&self, base: Complex64, exponent: Complex64) -> Result<Complex64, Fault> {
+        if base == Complex64::default() {
+            return if exponent.im == 0.0 && exponent.re < 0.0 {
+                Err(SolveError::DivisionByZero)
+            } else if exponent == Complex64::default() {
+                Ok(Complex64::new(1.0, 0.0))
+            } else {
+                Ok(base)
+            };
+        }
+        //powi keeps i^2 exactly -1
+        if exponent.im == 0.0 && exponent.re.fract() == 0.0 && exponent.re.abs() <= i32::MAX as f64
+        {
+            check(base.powi(exponent.re as i32))
+        } else {
+            check(base.powc(exponent))
+        }
+    }
+
+    fn nroot(&self, index: Complex64, radicand: Complex64) -> Result<Complex64, Fault> {
+        if index == Complex64::default() {
+            return Err(SolveError::DivisionByZero);
+        }
+        //odd roots of negative numbers stay real like they are without complex numbers
+        let odd = index.im == 0.0 && index.re.fract() == 0.0 && index.re % 2.0 != 0.0;
+        if odd && radicand.im == 0.0 && radicand.re < 0.0 {
+            return check(Complex64::from(-(-radicand.re).powf(1.0 / index.re)));
+        }
+        self.power(radicand, Complex64::new(1.0, 0.0) / index)
+    }
+}
+
+impl Backend for Complex {
+    type Value = Complex64;
+
+    fn parse(&self, num: &str) -> Option<Complex64> {
+        parse_complex(num)
+    }
+
+    fn constant(&self, constant: &Item) -> Result<Complex64, Fault> {
+        match constant {
+            Pi => Ok(Complex64::from(PI)),
+            E => Ok(Complex64::from(std::f64::consts::E)),
+            I => Ok(Complex64::i()),
+            _ => Err(SolveError::UnsupportedOperator),
+        }
+    }
+
+    fn operate(
+        &self,
+        operation: &Item,
+        value1: Complex64,
+        value2: Complex64,
+    ) -> Result<Complex64, Fault> {
+        match operation {
+            Add => check(value1 + value2),
+            Subtract => check(value1 - value2),
+            Multiply => check(value1 * value2),
+            Divide if value2 == Complex64::default() => Err(SolveError::DivisionByZero),
+            Divide => check(value1 / value2),
+            IntegerDivide if value1.im == 0.0 && value2.im == 0.0 => Float
+                .operate(operation, value1.re, value2.re)
+                .map(Complex64::from),
+            IntegerDivide => Err(SolveError::Domain),
+            Power => self.power(value1, value2),
+            Nroot => self.nroot(value1, value2),
+            _ => Err(SolveError::UnsupportedOperator),
+        }
+    }
+
+    fn function(
+        &self,
+        function: &Item,
+        value: Complex64,
+        degrees: bool,
+    ) -> Result<Complex64, Fault> {
+        let to_radians = |value: Complex64| if degrees { value * PI / 180.0 } else { value };
+        let from_radians = |value: Complex64| if degrees { value * 180.0 / PI } else { value };
+        let result = match function {
+            Sin => to_radians(value).sin(),
+            Cos => to_radians(value).cos(),
+            Tan => to_radians(value).tan(),
+            Ln | Log if value == Complex64::default() => return Err(SolveError::Domain),
+            Ln => value.ln(),
+            Log => value.log10(),
+            Sqrt => value.sqrt(),
+            Asin => from_radians(value.asin()),
+            Acos => from_radians(value.acos()),
+            Atan => from_radians(value.atan()),
+            Sinh => value.sinh(),
+            Cosh => value.cosh(),
+            Tanh => value.tanh(),
+            Asinh => value.asinh(),
+            Acosh => value.acosh(),
+            Atanh if value == Complex64::from(1.0) || value == Complex64::from(-1.0) => {
+                return Err(SolveError::Domain)
+            }
+            Atanh => value.atanh(),
+            //the special functions only take real values
+            Erf | Erfc | Gamma | Lgamma | Digamma | NormCdf | NormInv if value.im == 0.0 => {
+                return Float
+                    .function(function, value.re, degrees)
+                    .map(Complex64::from)
+            }
+            Erf | Erfc | Gamma | Lgamma | Digamma | NormCdf | NormInv => {
+                return Err(SolveError::Domain)
+            }
+            Abs => Complex64::from(value.norm()),
+            //the direction of the value, which is 0 for 0
+            Sign if value == Complex64::default() => value,
+            Sign => value / value.norm(),
+            Floor | Ceil | Trunc if value.im == 0.0 => {
+                return Float
+                    .function(function, value.re, degrees)
+                    .map(Complex64::from)
+            }
+            Floor | Ceil | Trunc => return Err(SolveError::Domain),
+            _ => return Err(SolveError::UnsupportedOperator),
+        };
+        check(result)
+    }
+
+    fn nary_function(&self, function: &Item, values: Vec<Complex64>) -> Result<Complex64, Fault> {
+        match (function, values.as_slice()) {
+            //negative bases and values have complex logs
+            (Log, [base, value]) => {
+                let one = Complex64::new(1.0, 0.0);
+                if *base == Complex64::default() || *base == one || *value == Complex64::default() {
+                    return Err(SolveError::Domain);
+                }
+                check(value.ln() / base.ln())
+            }
+            _ if values.iter().all(|value| value.im == 0.0) => {
+                let values = values.iter().map(|value| value.re).collect();
+                Float.nary_function(function, values).map(Complex64::from)
+            }
+            _ => Err(SolveError::Domain),
+        }
+    }
+
+    fn factorial(&self, value: Complex64) -> Result<Complex64, Fault> {
+        if value.im != 0.0 {
+            return Err(SolveError::Domain);
+        }
+        Float.factorial(value.re).map(Complex64::from)
+    }
+}
+
+fn check(value: Complex64) -> Result<Complex64, Fault> {
+    if value.is_nan() {
+        return Err(SolveError::Domain);
+    } else if value.is_infinite() {
+        return Err(SolveError::Overflow);
+    }
+
+    let mut value = value;
+    if value.im.abs() < value.re.abs() * TIDY_RATIO {
+        value.im = 0.0;
+    } else if value.re.abs() < value.im.abs() * TIDY_RATIO {
+        value.re = 0.0;
+    }
+    Ok(value)
+}
+
+//reads answers written by format_complex, like 3 - 4i
+fn parse_complex(num: &str) -> Option<Complex64> {
+    let num = num.replace([' ', '–'], "").replacen("+-", "-", 1);
+    let Some(body) = num.strip_suffix('i') else {
+        return Float.parse(&num).map(Complex64::from);
+    };
+
+    //the sign between the parts, skipping the one in an exponent like 1e+20
+    let split = body
+        .char_indices()
+        .rev()
+        .find(|(index, c)| {
+            matches!(c, '+' | '-') && *index > 0 && !body[..*index].ends_with(['e', 'E'])
+        })
+        .map(|(index, _)| index);
+    let (real, imaginary) = match split {
+        Some(index) => (Float.parse(&body[..index])?, &body[index..]),
+        None => (0.0, body),
+    };
+    let imaginary = match imaginary {
+        "" | "+" => 1.0,
+        "-" => -1.0,
+        _ => Float.parse(imaginary.trim_start_matches('+'))?,
+    };
+    Some(Complex64::new(real, imaginary))
+}
+
+pub fn format_complex(value: Complex64, display: ComplexDisplay, degrees: bool) -> String {
+    if value.im == 0.0 {
+        return format_number(value.re);
+    }
+
+    match display {
+        ComplexDisplay::Rectangular => {
+            let imaginary = if value.im.abs() == 1.0 {
+                "i".to_string()
+            } else {
+                format!("{}i", format_number(value.im.abs()))
+            };
+            let sign = if value.im < 0.0 { "-" } else { "" };
+            if value.re == 0.0 {
+                format!("{sign}{imaginary}")
+            } else if value.im < 0.0 {
+                format!("{} - {imaginary}", format_number(value.re))
+            } else {
+                format!("{} + {imaginary}", format_number(value.re))
+            }
+        }
+        ComplexDisplay::Polar => {
+            let (magnitude, angle) = value.to_polar();
+            if degrees {
+                format!(
+                    "{}∠{}°",
+                    format_number(magnitude),
+                    format_number(angle.to_degrees())
+                )
+            } else {
+                format!("{}∠{}", format_number(magnitude), format_number(angle))
+            }
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use serde::Deserialize;
+
+//exchange rates read from a file the user keeps up to date, nothing is downloaded
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Rates {
+    //how much of each currency one of the base currency buys, so the base itself is 1
+    pub rates: BTreeMap<String, f64>,
+    pub base: Option<String>,
+    //when the rates are from, as written in the file or when the file was last changed
+    pub updated: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum RatesError {
+    //the file couldn't be opened, with the reason
+    Read(String),
+    //the line of the file that isn't a rate
+    Invalid(usize),
+    //a code that isn't three capital letters or a rate that isn't a positive number
+    BadRate(String),
+}
+
+impl fmt::Display for RatesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RatesError::Read(reason) => write!(f, "Couldn't read the rates file: {reason}"),
+            RatesError::Invalid(line) => write!(f, "Invalid rates file at line {line}"),
+            RatesError::BadRate(code) => write!(f, "Bad exchange rate for {code}"),
+        }
+    }
+}
+
+//the ISO 4217 codes, in order so they can be binary searched
+const CURRENCIES: &[&str] = &[
+    "AED", "AFN", "ALL", "AMD", "ANG", "AOA", "ARS", "AUD", "AWG", "AZN", "BAM", "BBD", "BDT",
+    "BGN", "BHD", "BIF", "BMD", "BND", "BOB", "BRL", "BSD", "BTN", "BWP", "BYN", "BZD", "CAD",
+    "CDF", "CHF", "CLP", "CNY", "COP", "CRC", "CUP", "CVE", "CZK", "DJF", "DKK", "DOP", "DZD",
+    "EGP", "ERN", "ETB", "EUR", "FJD", "FKP", "GBP", "GEL", "GHS", "GIP", "GMD", "GNF", "GTQ",
+    "GYD", "HKD", "HNL", "HTG", "HUF", "IDR", "ILS", "INR", "IQD", "IRR", "ISK", "JMD", "JOD",
+    "JPY", "KES", "KGS", "KHR", "KMF", "KPW", "KRW", "KWD", "KYD", "KZT", "LAK", "LBP", "LKR",
+    "LRD", "LSL", "LYD", "MAD", "MDL", "MGA", "MKD", "MMK", "MNT", "MOP", "MRU", "MUR", "MVR",
+    "MWK", "MXN", "MYR", "MZN", "NAD", "NGN", "NIO", "NOK", "NPR", "NZD", "OMR", "PAB", "PEN",
+    "PGK", "PHP", "PKR", "PLN", "PYG", "QAR", "RON", "RSD", "RUB", "RWF", "SAR", "SBD", "SCR",
+    "SDG", "SEK", "SGD", "SHP", "SLE", "SLL", "SOS", "SRD", "SSP", "STN", "SVC", "SYP", "SZL",
+    "THB", "TJS", "TMT", "TND", "TOP", "TRY", "TTD", "TWD", "TZS", "UAH", "UGX", "USD", "UYU",
+    "UZS", "VES", "VND", "VUV", "WST", "XAF", "XAG", "XAU", "XCD", "XCG", "XDR", "XOF", "XPD",
+    "XPF", "XPT", "YER", "ZAR", "ZMW", "ZWG", "ZWL",
+];
+
+//currencies are written with their ISO 4217 code like USD, other capitals like ABC are still variables
+pub fn is_currency(name: &str) -> bool {
+    CURRENCIES.binary_search(&name).is_ok()
+}
+
+//rates files can have codes that aren't ISO 4217 like BTC, they're read but can't be typed
+fn is_code(code: &str) -> bool {
+    code.len() == 3 && code.chars().all(|c| c.is_ascii_uppercase())
+}
+
+impl Rates {
+    //what one of the currency is worth in the base currency
+    pub fn value(&self, code: &str) -> Option<f64> {
+        self.rates.get(code).map(|rate| 1.0 / rate)
+    }
+
+    fn insert(&mut self, code: &str, rate: f64) -> Result<(), RatesError> {
+        if !is_code(code) || !rate.is_finite() || rate <= 0.0 {
+            return Err(RatesError::BadRate(code.into()));
+        }
+        self.rates.insert(code.into(), rate);
+        Ok(())
+    }
+
+    fn set_base(&mut self, base: &str) -> Result<(), RatesError> {
+        if !is_code(base) {
+            return Err(RatesError::BadRate(base.into()));
+        }
+        self.rates.entry(base.into()).or_insert(1.0);
+        self.base = Some(base.into());
+        Ok(())
+    }
+}
+
+//json files look like the ones exchange rate sites give out
+//{"base": "USD", "timestamp": 1760745600, "rates": {"EUR": 0.86, "GBP": 0.75}}
+#[derive(Deserialize)]
+struct RatesFile {
+    base: Option<String>,
+    #[serde(alias = "date")]
+    timestamp: Option<Timestamp>,
+    rates: BTreeMap<String, f64>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Timestamp {
+    //unix time
+    Seconds(u64),
+    Text(String),
+}
+
+pub fn parse_json(text: &str) -> Result<Rates, RatesError> {
+    let file: RatesFile =
+        serde_json::from_str(text).map_err(|error| RatesError::Invalid(error.line()))?;
+    let mut rates = Rates {
+        updated: file.timestamp.map(|timestamp| match timestamp {
+            Timestamp::Seconds(seconds) => format_time(seconds),
+            Timestamp::Text(text) => text,
+        }),
+        ..Default::default()
+    };
+    for (code, rate) in file.rates {
+        rates.insert(&code, rate)?;
+    }
+    if let Some(base) = file.base {
+        rates.set_base(&base)?;
+    }
+    Ok(rates)
+}
+
+//a currency and its rate on each line, with base and timestamp lines and an optional header
+//base,USD
+//timestamp,2026-10-18
+//EUR,0.86
+pub fn parse_csv(text: &str) -> Result<Rates, RatesError> {
+    let mut rates = Rates::default();
+    let mut header = true;
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (key, value) = line
+            .split_once([',', ';'])
+            .map(|(key, value)| (key.trim(), value.trim()))
+            .ok_or(RatesError::Invalid(index + 1))?;
+        match key {
+            "base" => rates.set_base(value)?,
+            "timestamp" | "date" => rates.updated = Some(value.into()),
+            _ => match value.parse() {
+                Ok(rate) => rates.insert(key, rate)?,
+                //like currency,rate
+                Err(_) if header => {}
+                Err(_) => return Err(RatesError::Invalid(index + 1)),
+            },
+        }
+        header = false;
+    }
+    Ok(rates)
+}
+
+//json or csv by the extension, or by whether it looks like json
+pub fn load_rates(path: &Path) -> Result<Rates, RatesError> {
+    let text = fs::read_to_string(path).map_err(|error| RatesError::Read(error.to_string()))?;
+    let json = match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) => extension.eq_ignore_ascii_case("json"),
+        None => text.trim_start().starts_with('{'),
+    };
+    let mut rates = if json {
+        parse_json(&text)?
+    } else {
+        parse_csv(&text)?
+    };
+
+    if rates.updated.is_none() {
+        rates.updated = fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map(|since| format_time(since.as_secs()));
+    }
+    Ok(rates)
+}
+
+//unix time as a date like 2025-10-18 00:00 UTC
+//https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+pub fn format_time(seconds: u64) -> String {
+    let days = (seconds / 86400) as i64 + 719468;
+    let minutes = seconds % 86400 / 60;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    //months are counted from march so leap days come last
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{year}-{month:02}-{day:02} {:02}:{:02} UTC",
+        minutes / 60,
+        minutes % 60
+    )
+}
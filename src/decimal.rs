@@ -0,0 +1,554 @@
+use std::str::FromStr;
+
+use dashu_float::ops::SquareRoot;
+use dashu_float::DBig;
+use dashu_int::ops::{Gcd, UnsignedAbs};
+use dashu_int::IBig;
+
+use crate::calculator::Item;
+use crate::programmer::{parse_integer, Radix};
+use crate::solver::{Backend, Fault, Float, SolveError};
+use Item::*;
+
+//extra digits carried through a calculation so rounding errors stay out of the shown ones
+const GUARD_DIGITS: usize = 10;
+//factorials are calculated exactly, so bigger ones would take too long
+const MAX_FACTORIAL: u32 = 10_000;
+//angles with more digits than this before the point are too big to take whole turns out of
+const MAX_TURN_DIGITS: f64 = 1000.0;
+//results with a bigger power of ten than this are treated as an overflow
+pub(crate) const MAX_EXPONENT: f64 = 1_000_000.0;
+
+//solves with decimal numbers that are accurate to the given number of significant digits
+pub struct Decimal {
+    pub digits: usize,
+}
+
+impl Decimal {
+    fn precision(&self) -> usize {
+        self.digits.max(1) + GUARD_DIGITS
+    }
+
+    fn number(&self, num: i64) -> DBig {
+        DBig::from(num).with_precision(self.precision()).value()
+    }
+
+    //anything smaller than this is considered a rounding error
+    fn epsilon(&self) -> DBig {
+        DBig::from_parts(IBig::ONE, -(self.precision() as isize))
+    }
+
+    //machin's formula
+    pub fn pi(&self) -> DBig {
+        let one = self.number(1);
+        self.number(16) * self.atan_series(&one / self.number(5))
+            - self.number(4) * self.atan_series(&one / self.number(239))
+    }
+
+    pub fn e(&self) -> DBig {
+        self.number(1).exp()
+    }
+
+    //only converges quickly when the value is small
+    fn atan_series(&self, value: DBig) -> DBig {
+        let epsilon = self.epsilon();
+        let squared = &value * &value;
+        let mut power = value.clone();
+        let mut sum = value;
+        let mut n = 1;
+
+        loop {
+            power = -(power * &squared);
+            let term = &power / self.number(2 * n + 1);
+            if abs(&term) < epsilon {
+                return sum;
+            }
+            sum += term;
+            n += 1;
+        }
+    }
+
+    fn atan(&self, value: DBig) -> DBig {
+        let one = self.number(1);
+        if abs(&value) > one {
+            //atan(x) = ±π/2 - atan(1/x)
+            let half_pi = self.pi() / self.number(2);
+            let rest = self.atan(&one / &value);
+            return if value < DBig::ZERO {
+                -half_pi - rest
+            } else {
+                half_pi - rest
+            };
+        }
+
+        //atan(x) = 2atan(x / (1 + √(1 + x²))) until the series converges quickly
+        let limit = self.number(1) / self.number(100);
+        let mut value = value;
+        let mut doublings = 0;
+        while abs(&value) > limit {
+            value = &value / (&one + (&one + &value * &value).sqrt());
+            doublings += 1;
+        }
+        self.atan_series(value) * self.number(2).powi(doublings.into())
+    }
+
+    //sin(x) when cosine is false and cos(x) when it's true, x has to be between -π and π
+    fn sin_cos_series(&self, value: DBig, cosine: bool) -> DBig {
+        let epsilon = self.epsilon();
+        let squared = &value * &value;
+        let mut term = if cosine { self.number(1) } else { value };
+        let mut sum = term.clone();
+        let mut n = if cosine { 0 } else { 1 };
+
+        //terms of π^n/n! are below epsilon well before this
+        while n < 4 * self.precision() as i64 + 100 {
+            term = -(term * &squared) / self.number((n + 1) * (n + 2));
+            if abs(&term) < epsilon {
+                break;
+            }
+            sum += &term;
+            n += 2;
+        }
+        sum
+    }
+
+    fn sin_cos(&self, value: DBig, degrees: bool, cosine: bool) -> Result<DBig, Fault> {
+        //big values need more digits to take the turns out, so they don't swallow the ones left over
+        let magnitude = value.to_f64().value().abs().log10().max(0.0).ceil();
+        if magnitude > MAX_TURN_DIGITS {
+            return Err(SolveError::Overflow);
+        }
+        let wide = Decimal {
+            digits: self.digits + magnitude as usize,
+        };
+        let value = value.with_precision(wide.precision()).value();
+        let radians = if degrees {
+            //turns are exact in degrees so sin(180) stays 0
+            let turn = wide.number(360);
+            let turns = (&value / &turn).floor();
+            let value = (value - turns * turn)
+                .with_precision(self.precision())
+                .value();
+            value * self.pi() / self.number(180)
+        } else {
+            let turn = wide.pi() * wide.number(2);
+            let turns = (&value / &turn).round();
+            (value - turns * turn)
+                .with_precision(self.precision())
+                .value()
+        };
+
+        let result = self.sin_cos_series(radians, cosine);
+        if abs(&result) < self.epsilon() * self.number(1000) {
+            Ok(DBig::ZERO.with_precision(self.precision()).value())
+        } else {
+            Ok(result)
+        }
+    }
+
+    //sinh(x) when cosine is false and cosh(x) when it's true
+    fn sinh_cosh(&self, value: &DBig, cosine: bool) -> Result<DBig, Fault> {
+        //e^x overflows at the same size 10^x does
+        if abs(value).to_f64().value() / std::f64::consts::LN_10 > MAX_EXPONENT {
+            return Err(SolveError::Overflow);
+        }
+
+        let one = self.number(1);
+        if !cosine && abs(value) < one {
+            //e^x - e^-x cancels out the digits of small values
+            let epsilon = self.epsilon();
+            let squared = value * value;
+            let mut term = value.clone();
+            let mut sum = term.clone();
+            let mut n = 1;
+            loop {
+                term = term * &squared / self.number((n + 1) * (n + 2));
+                if abs(&term) < epsilon {
+                    return Ok(sum);
+                }
+                sum += &term;
+                n += 2;
+            }
+        }
+
+        let exp = value.exp();
+        let inverse = &one / &exp;
+        if cosine {
+            Ok((exp + inverse) / self.number(2))
+        } else {
+            Ok((exp - inverse) / self.number(2))
+        }
+    }
+
+    fn tanh(&self, value: &DBig) -> Result<DBig, Fault> {
+        //past this tanh is ±1 to every digit
+        if abs(value).to_f64().value() > self.precision() as f64 {
+            return Ok(if *value < DBig::ZERO {
+                -self.number(1)
+            } else {
+                self.number(1)
+            });
+        }
+        Ok(self.sinh_cosh(value, false)? / self.sinh_cosh(value, true)?)
+    }
+
+    //there's no arbitrary precision version of the special functions,
+    //so they're only as accurate as a float
+    fn float(&self, value: f64) -> Result<DBig, Fault> {
+        self.parse(&value.to_string()).ok_or(SolveError::Domain)
+    }
+
+    fn radians_to_angle(&self, value: DBig, degrees: bool) -> DBig {
+        if degrees {
+            value * self.number(180) / self.pi()
+        } else {
+            value
+        }
+    }
+
+    fn asin(&self, value: DBig) -> Result<DBig, Fault> {
+        let one = self.number(1);
+        match abs(&value).cmp(&one) {
+            std::cmp::Ordering::Greater => Err(SolveError::Domain),
+            std::cmp::Ordering::Equal => Ok(self.pi() / self.number(2) * value),
+            std::cmp::Ordering::Less => Ok(self.atan(&value / (&one - &value * &value).sqrt())),
+        }
+    }
+
+    fn power(&self, base: DBig, exponent: DBig) -> Result<DBig, Fault> {
+        if base.repr().is_zero() {
+            return if exponent < DBig::ZERO {
+                Err(SolveError::DivisionByZero)
+            } else if exponent.repr().is_zero() {
+                Ok(self.number(1))
+            } else {
+                Ok(base)
+            };
+        }
+
+        let magnitude = abs(&base).to_f64().value().log10() * exponent.to_f64().value();
+        if magnitude > MAX_EXPONENT {
+            return Err(SolveError::Overflow);
+        } else if magnitude < -MAX_EXPONENT {
+            return Ok(DBig::ZERO.with_precision(self.precision()).value());
+        }
+
+        if exponent.repr().is_int() {
+            Ok(base.powi(exponent.to_int().value()))
+        } else if base < DBig::ZERO {
+            Err(SolveError::Domain)
+        } else {
+            Ok(base.powf(&exponent))
+        }
+    }
+
+    fn nroot(&self, index: DBig, radicand: DBig) -> Result<DBig, Fault> {
+        if index.repr().is_zero() {
+            return Err(SolveError::DivisionByZero);
+        }
+        let reciprocal = self.number(1) / &index;
+        if radicand < DBig::ZERO {
+            let odd = index.repr().is_int() && !(&index / self.number(2)).repr().is_int();
+            if !odd {
+                return Err(SolveError::Domain);
+            }
+            return Ok(-self.power(-radicand, reciprocal)?);
+        }
+        self.power(radicand, reciprocal)
+    }
+}
+
+impl Backend for Decimal {
+    type Value = DBig;
+
+    fn parse(&self, num: &str) -> Option<DBig> {
+        match num.split_once('/') {
+            Some((numerator, denominator)) => {
+                let denominator = self.parse(denominator)?;
+                if denominator.repr().is_zero() {
+                    return None;
+                }
+                Some(self.parse(numerator)? / denominator)
+            }
+            None => parse_decimal(num).map(|value| value.with_precision(self.precision()).value()),
+        }
+    }
+
+    fn constant(&self, constant: &Item) -> Result<DBig, Fault> {
+        match constant {
+            Pi => Ok(self.pi()),
+            E => Ok(self.e()),
+            I => Err(SolveError::Domain),
+            _ => Err(SolveError::UnsupportedOperator),
+        }
+    }
+
+    fn operate(&self, operation: &Item, value1: DBig, value2: DBig) -> Result<DBig, Fault> {
+        match operation {
+            Add => Ok(value1 + value2),
+            Subtract => Ok(value1 - value2),
+            Multiply => Ok(value1 * value2),
+            Divide if value2.repr().is_zero() => Err(SolveError::DivisionByZero),
+            Divide => Ok(value1 / value2),
+            IntegerDivide if value2.repr().is_zero() => Err(SolveError::DivisionByZero),
+            IntegerDivide => Ok((value1 / value2).floor()),
+            Power => self.power(value1, value2),
+            Nroot => self.nroot(value1, value2),
+            _ => Err(SolveError::UnsupportedOperator),
+        }
+    }
+
+    fn function(&self, function: &Item, value: DBig, degrees: bool) -> Result<DBig, Fault> {
+        match function {
+            Sin => self.sin_cos(value, degrees, false),
+            Cos => self.sin_cos(value, degrees, true),
+            Tan => {
+                let cos = self.sin_cos(value.clone(), degrees, true)?;
+                if cos.repr().is_zero() {
+                    return Err(SolveError::Domain);
+                }
+                Ok(self.sin_cos(value, degrees, false)? / cos)
+            }
+            Ln | Log if value <= DBig::ZERO => Err(SolveError::Domain),
+            Ln => Ok(value.ln()),
+            Log => Ok(value.ln() / self.number(10).ln()),
+            Sqrt if value < DBig::ZERO => Err(SolveError::Domain),
+            Sqrt => Ok(value.sqrt()),
+            Asin => Ok(self.radians_to_angle(self.asin(value)?, degrees)),
+            Acos => {
+                let asin = self.asin(value)?;
+                Ok(self.radians_to_angle(self.pi() / self.number(2) - asin, degrees))
+            }
+            Atan => Ok(self.radians_to_angle(self.atan(value), degrees)),
+            Sinh => self.sinh_cosh(&value, false),
+            Cosh => self.sinh_cosh(&value, true),
+            Tanh => self.tanh(&value),
+            Asinh => {
+                //ln(x + √(x² + 1)) loses the digits of big negative values
+                let one = self.number(1);
+                let asinh = |value: &DBig| (value + (value * value + &one).sqrt()).ln();
+                if value < DBig::ZERO {
+                    Ok(-asinh(&-value))
+                } else {
+                    Ok(asinh(&value))
+                }
+            }
+            Acosh if value < self.number(1) => Err(SolveError::Domain),
+            Acosh => Ok((&value + (&value * &value - self.number(1)).sqrt()).ln()),
+            Atanh if abs(&value) >= self.number(1) => Err(SolveError::Domain),
+            Atanh => {
+                let one = self.number(1);
+                Ok(((&one + &value) / (&one - &value)).ln() / self.number(2))
+            }
+            Gamma if value > DBig::ZERO && value.repr().is_int() => {
+                self.factorial(value - self.number(1))
+            }
+            Erf | Erfc | Gamma | Lgamma | Digamma | NormCdf | NormInv => {
+                self.float(Float.function(function, value.to_f64().value(), degrees)?)
+            }
+            Floor => Ok(value.floor()),
+            Ceil => Ok(value.ceil()),
+            Trunc => Ok(value.trunc()),
+            Abs => Ok(abs(&value)),
+            Sign if value.repr().is_zero() => Ok(self.number(0)),
+            Sign if value < DBig::ZERO => Ok(self.number(-1)),
+            Sign => Ok(self.number(1)),
+            _ => Err(SolveError::UnsupportedOperator),
+        }
+    }
+
+    fn nary_function(&self, function: &Item, values: Vec<DBig>) -> Result<DBig, Fault> {
+        let integers = || -> Result<Vec<IBig>, Fault> {
+            values
+                .iter()
+                .map(|value| {
+                    if value.repr().is_int() {
+                        Ok(value.to_int().value())
+                    } else {
+                        Err(SolveError::Domain as Fault)
+                    }
+                })
+                .collect()
+        };
+        let integer = |value: IBig| DBig::from(value).with_precision(self.precision()).value();
+
+        match (function, values.as_slice()) {
+            (Min, _) => values.iter().min().cloned().ok_or(SolveError::Syntax),
+            (Max, _) => values.iter().max().cloned().ok_or(SolveError::Syntax),
+            (Gcd, _) => Ok(integer(integers()?.iter().fold(IBig::ZERO, integer_gcd))),
+            (Lcm, _) => Ok(integer(integers()?.iter().fold(IBig::ONE, integer_lcm))),
+            (Ncr | Npr, _) => {
+                let integers = integers()?;
+                let ordered = *function == Npr;
+                Ok(integer(integer_choose(
+                    &integers[0],
+                    &integers[1],
+                    ordered,
+                )?))
+            }
+            (Log, [base, value]) => {
+                let one = self.number(1);
+                if *base <= DBig::ZERO || *base == one || *value <= DBig::ZERO {
+                    return Err(SolveError::Domain);
+                }
+                Ok(value.ln() / base.ln())
+            }
+            (Round, [value]) => Ok(value.round()),
+            (Round, [value, places]) => {
+                if !places.repr().is_int() {
+                    return Err(SolveError::Domain);
+                }
+                let places = places.to_f64().value().clamp(-MAX_EXPONENT, MAX_EXPONENT);
+                let scale = DBig::from_parts(IBig::ONE, places as isize);
+                Ok((value * &scale).round() / scale)
+            }
+            (Mod, [_, divisor]) if divisor.repr().is_zero() => Err(SolveError::DivisionByZero),
+            (Mod, [value, divisor]) => Ok(value - divisor * (value / divisor).floor()),
+            (Beta, _) => {
+                let values = values.iter().map(|value| value.to_f64().value()).collect();
+                self.float(Float.nary_function(function, values)?)
+            }
+            _ => Err(SolveError::UnsupportedOperator),
+        }
+    }
+
+    fn factorial(&self, value: DBig) -> Result<DBig, Fault> {
+        if !value.repr().is_int() {
+            //there's no exact gamma function, so non integers are only as accurate as a float
+            let result = statrs::function::gamma::gamma(value.to_f64().value() + 1.0);
+            if result.is_nan() {
+                return Err(SolveError::Domain);
+            } else if result.is_infinite() {
+                return Err(SolveError::Overflow);
+            }
+            return self.parse(&result.to_string()).ok_or(SolveError::Domain);
+        }
+        let product = integer_factorial(&value.to_int().value())?;
+        Ok(DBig::from(product).with_precision(self.precision()).value())
+    }
+}
+
+//answers shown in scientific notation are written as 1.5e+30
+pub(crate) fn parse_decimal(num: &str) -> Option<DBig> {
+    //hexadecimal, octal and binary numbers from programmer mode are whole
+    if Radix::of(num) != Radix::Decimal {
+        return parse_integer(num).map(DBig::from);
+    }
+    DBig::from_str(&num.replace("e+", "e")).ok()
+}
+
+pub(crate) fn integer_factorial(value: &IBig) -> Result<IBig, Fault> {
+    if *value < IBig::ZERO {
+        return Err(SolveError::Domain);
+    }
+    let n = u32::try_from(value).map_err(|_| SolveError::Overflow as Fault)?;
+    if n > MAX_FACTORIAL {
+        return Err(SolveError::Overflow);
+    }
+    Ok((2..=n).fold(IBig::ONE, |product, i| product * IBig::from(i)))
+}
+
+pub(crate) fn integer_choose(n: &IBig, r: &IBig, ordered: bool) -> Result<IBig, Fault> {
+    if *n < IBig::ZERO || *r < IBig::ZERO {
+        return Err(SolveError::Domain);
+    } else if r > n {
+        return Ok(IBig::ZERO);
+    }
+
+    let n_minus_r = n - r;
+    let terms = if ordered || r < &n_minus_r {
+        r
+    } else {
+        &n_minus_r
+    };
+    let terms = u32::try_from(terms).map_err(|_| SolveError::Overflow as Fault)?;
+    if terms > MAX_FACTORIAL {
+        return Err(SolveError::Overflow);
+    }
+
+    let mut result = IBig::ONE;
+    for i in 0..terms {
+        result *= n - IBig::from(i);
+        if !ordered {
+            result /= IBig::from(i + 1);
+        }
+    }
+    Ok(result)
+}
+
+pub(crate) fn integer_gcd(a: IBig, b: &IBig) -> IBig {
+    if a == IBig::ZERO {
+        IBig::from(b.unsigned_abs())
+    } else if *b == IBig::ZERO {
+        IBig::from(a.unsigned_abs())
+    } else {
+        IBig::from((&a).gcd(b))
+    }
+}
+
+pub(crate) fn integer_lcm(a: IBig, b: &IBig) -> IBig {
+    if a == IBig::ZERO || *b == IBig::ZERO {
+        return IBig::ZERO;
+    }
+    let gcd = integer_gcd(a.clone(), b);
+    IBig::from((a / gcd * b).unsigned_abs())
+}
+
+fn abs(value: &DBig) -> DBig {
+    if *value < DBig::ZERO {
+        -value
+    } else {
+        value.clone()
+    }
+}
+
+//rounds to the number of significant digits, switching to scientific notation when they run out
+pub fn format_decimal(value: &DBig, digits: usize) -> String {
+    let digits = digits.max(1);
+    let rounded = value.clone().with_precision(digits).value();
+    let repr = rounded.repr();
+    if repr.is_zero() {
+        return "0".into();
+    }
+
+    let negative = *repr.significand() < IBig::ZERO;
+    let mut significand = repr.significand().to_string();
+    significand.retain(|c| c.is_ascii_digit());
+    let mut exponent = repr.exponent();
+    while significand.len() > 1 && significand.ends_with('0') {
+        significand.pop();
+        exponent += 1;
+    }
+
+    //the power of ten of the first digit
+    let scientific_exponent = significand.len() as isize - 1 + exponent;
+    let mut text = if negative {
+        String::from("-")
+    } else {
+        String::new()
+    };
+
+    if scientific_exponent >= digits as isize || scientific_exponent < -(digits as isize) {
+        text.push_str(&significand[..1]);
+        if significand.len() > 1 {
+            text.push('.');
+            text.push_str(&significand[1..]);
+        }
+        let sign = if scientific_exponent < 0 { '-' } else { '+' };
+        text.push_str(&format!("e{sign}{}", scientific_exponent.abs()));
+    } else if exponent >= 0 {
+        text.push_str(&significand);
+        text.push_str(&"0".repeat(exponent as usize));
+    } else if scientific_exponent >= 0 {
+        let point = (scientific_exponent + 1) as usize;
+        text.push_str(&significand[..point]);
+        text.push('.');
+        text.push_str(&significand[point..]);
+    } else {
+        text.push_str("0.");
+        text.push_str(&"0".repeat((-scientific_exponent - 1) as usize));
+        text.push_str(&significand);
+    }
+
+    text
+}
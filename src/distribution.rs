@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+use statrs::distribution::{
+    Binomial, ChiSquared, Continuous, ContinuousCDF, Discrete, DiscreteCDF, Exp, Normal, Poisson,
+    StudentsT,
+};
+
+use crate::solver::SolveError;
+
+//halving the interval this many times gets as close as a float can
+const BISECTIONS: usize = 200;
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum Distribution {
+    #[default]
+    Normal,
+    StudentsT,
+    ChiSquared,
+    Binomial,
+    Poisson,
+    Exponential,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum Query {
+    //the pdf, or the pmf of discrete distributions
+    #[default]
+    Density,
+    Cumulative,
+    Inverse,
+}
+
+impl Distribution {
+    pub const ALL: [Distribution; 6] = [
+        Distribution::Normal,
+        Distribution::StudentsT,
+        Distribution::ChiSquared,
+        Distribution::Binomial,
+        Distribution::Poisson,
+        Distribution::Exponential,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Distribution::Normal => "Normal",
+            Distribution::StudentsT => "Student's t",
+            Distribution::ChiSquared => "Chi-squared",
+            Distribution::Binomial => "Binomial",
+            Distribution::Poisson => "Poisson",
+            Distribution::Exponential => "Exponential",
+        }
+    }
+
+    //the names of the parameters and their usual values
+    pub fn parameters(self) -> &'static [(&'static str, f64)] {
+        match self {
+            Distribution::Normal => &[("μ", 0.0), ("σ", 1.0)],
+            Distribution::StudentsT => &[("ν", 1.0)],
+            Distribution::ChiSquared => &[("k", 1.0)],
+            Distribution::Binomial => &[("n", 10.0), ("p", 0.5)],
+            Distribution::Poisson => &[("λ", 1.0)],
+            Distribution::Exponential => &[("λ", 1.0)],
+        }
+    }
+
+    pub fn is_discrete(self) -> bool {
+        matches!(self, Distribution::Binomial | Distribution::Poisson)
+    }
+
+    //x is a value of the distribution, or a probability for the inverse cdf
+    pub fn evaluate(self, parameters: &[f64], query: Query, x: f64) -> Result<f64, SolveError> {
+        let domain = |_| SolveError::Domain(0);
+        let parameter = |index: usize| parameters.get(index).copied().ok_or(SolveError::Syntax(0));
+        if query == Query::Inverse && !(x > 0.0 && x < 1.0) {
+            return Err(SolveError::Domain(0));
+        }
+
+        let result = match self {
+            Distribution::Normal => continuous(
+                &Normal::new(parameter(0)?, parameter(1)?).map_err(domain)?,
+                query,
+                x,
+            ),
+            Distribution::StudentsT => continuous(
+                &StudentsT::new(0.0, 1.0, parameter(0)?).map_err(domain)?,
+                query,
+                x,
+            ),
+            Distribution::ChiSquared => {
+                let distribution = ChiSquared::new(parameter(0)?).map_err(domain)?;
+                match query {
+                    //statrs only has a rough search for this one
+                    Query::Inverse => bisect(|x| distribution.cdf(x), x, 0.0),
+                    _ => continuous(&distribution, query, x),
+                }
+            }
+            Distribution::Exponential => {
+                let rate = parameter(0)?;
+                let distribution = Exp::new(rate).map_err(domain)?;
+                match query {
+                    Query::Inverse => -(1.0 - x).ln() / rate,
+                    _ => continuous(&distribution, query, x),
+                }
+            }
+            Distribution::Binomial => {
+                let n = parameter(0)?;
+                if n < 0.0 || n.fract() != 0.0 {
+                    return Err(SolveError::Domain(0));
+                }
+                discrete(
+                    &Binomial::new(parameter(1)?, n as u64).map_err(domain)?,
+                    query,
+                    x,
+                )
+            }
+            Distribution::Poisson => {
+                discrete(&Poisson::new(parameter(0)?).map_err(domain)?, query, x)
+            }
+        };
+
+        if result.is_nan() {
+            Err(SolveError::Domain(0))
+        } else if result.is_infinite() {
+            Err(SolveError::Overflow(0))
+        } else {
+            Ok(result)
+        }
+    }
+}
+
+fn continuous<D: Continuous<f64, f64> + ContinuousCDF<f64, f64>>(
+    distribution: &D,
+    query: Query,
+    x: f64,
+) -> f64 {
+    match query {
+        Query::Density => distribution.pdf(x),
+        Query::Cumulative => distribution.cdf(x),
+        Query::Inverse => distribution.inverse_cdf(x),
+    }
+}
+
+//values between the whole numbers have no probability of their own
+fn discrete<D: Discrete<u64, f64> + DiscreteCDF<u64, f64>>(
+    distribution: &D,
+    query: Query,
+    x: f64,
+) -> f64 {
+    match query {
+        Query::Density if x < 0.0 || x.fract() != 0.0 => 0.0,
+        Query::Density => distribution.pmf(x as u64),
+        Query::Cumulative if x < 0.0 => 0.0,
+        Query::Cumulative => distribution.cdf(x.floor() as u64),
+        Query::Inverse => discrete_inverse(distribution, x) as f64,
+    }
+}
+
+//statrs' own search can loop forever once its bounds are next to each other
+fn discrete_inverse<D: DiscreteCDF<u64, f64>>(distribution: &D, p: f64) -> u64 {
+    if distribution.cdf(0) >= p {
+        return 0;
+    }
+    //cdf(low) is always below p and cdf(high) never is
+    let mut low = 0;
+    let mut high = 1;
+    while distribution.cdf(high) < p && high < u64::MAX {
+        low = high;
+        high = high.saturating_mul(2);
+    }
+    while high - low > 1 {
+        let middle = low + (high - low) / 2;
+        if distribution.cdf(middle) < p {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    high
+}
+
+//the smallest x above low where cdf(x) reaches p
+fn bisect(cdf: impl Fn(f64) -> f64, p: f64, low: f64) -> f64 {
+    let mut low = low;
+    let mut high = low + 1.0;
+    while cdf(high) < p {
+        if high.is_infinite() {
+            return high;
+        }
+        high = low + (high - low) * 2.0;
+    }
+    for _ in 0..BISECTIONS {
+        let middle = (low + high) / 2.0;
+        if cdf(middle) < p {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    high
+}
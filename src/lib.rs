@@ -0,0 +1,28 @@
+pub mod answer;
+pub mod calculator;
+pub mod complex;
+pub mod currency;
+pub mod decimal;
+pub mod distribution;
+pub mod parser;
+pub mod programmer;
+pub mod rational;
+pub mod solver;
+pub mod statistics;
+pub mod symbolic;
+pub mod units;
+
+pub use answer::{solve_answer, Answer, AnswerFormat, Precision, Variables, MAX_DIGITS};
+pub use calculator::{format_number, push_history, Equation, Function, Functions, Item};
+pub use complex::ComplexDisplay;
+pub use currency::{load_rates, Rates, RatesError};
+pub use distribution::{Distribution, Query};
+pub use parser::{ParseError, Target};
+pub use programmer::Radix;
+pub use rational::FractionDisplay;
+pub use solver::{solve, SolveError};
+pub use statistics::Summary;
+pub use symbolic::exact_form;
+
+#[cfg(test)]
+pub mod tests;
@@ -1,12 +1,16 @@
 pub mod app;
-pub mod calculator;
 pub mod calculator_button;
-pub mod solver;
+pub mod cli;
+pub mod repl;
 use eframe::*;
 use egui::vec2;
 
-pub mod tests;
 fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if !args.is_empty() {
+        std::process::exit(cli::run(&args));
+    }
+
     let options = NativeOptions {
         initial_window_size: Some(vec2(760.5, 399.0)),
         transparent: true,
@@ -0,0 +1,358 @@
+use std::fmt;
+use std::ops::Range;
+
+use crate::calculator::Item;
+use crate::programmer::Radix;
+use crate::units::is_unit;
+use Item::*;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ParseError {
+    UnknownSymbol(Range<usize>),
+    UnexpectedSymbol(Range<usize>),
+    //assigning to a built in name like pi
+    ReservedName(Range<usize>),
+}
+
+impl ParseError {
+    //byte range of the offending text
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            ParseError::UnknownSymbol(span)
+            | ParseError::UnexpectedSymbol(span)
+            | ParseError::ReservedName(span) => span.clone(),
+        }
+    }
+
+    //the same error in text that starts offset bytes later
+    pub fn offset(self, offset: usize) -> Self {
+        let shift = |span: Range<usize>| span.start + offset..span.end + offset;
+        match self {
+            ParseError::UnknownSymbol(span) => ParseError::UnknownSymbol(shift(span)),
+            ParseError::UnexpectedSymbol(span) => ParseError::UnexpectedSymbol(shift(span)),
+            ParseError::ReservedName(span) => ParseError::ReservedName(shift(span)),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnknownSymbol(span) => write!(f, "Unknown symbol at {}", span.start),
+            ParseError::UnexpectedSymbol(span) => write!(f, "Unexpected symbol at {}", span.start),
+            ParseError::ReservedName(span) => write!(f, "Reserved name at {}", span.start),
+        }
+    }
+}
+
+//longer names have to come before their prefixes
+const NAMES: &[(&str, Item)] = &[
+    ("arsinh", Asinh),
+    ("arcosh", Acosh),
+    ("artanh", Atanh),
+    ("asinh", Asinh),
+    ("acosh", Acosh),
+    ("atanh", Atanh),
+    ("arcsin", Asin),
+    ("arccos", Acos),
+    ("arctan", Atan),
+    ("asin", Asin),
+    ("acos", Acos),
+    ("atan", Atan),
+    ("digamma", Digamma),
+    ("normcdf", NormCdf),
+    ("norminv", NormInv),
+    ("lgamma", Lgamma),
+    ("gamma", Gamma),
+    ("sqrt", Sqrt),
+    ("round", Round),
+    ("root", Nroot),
+    ("sinh", Sinh),
+    ("cosh", Cosh),
+    ("tanh", Tanh),
+    ("sin", Sin),
+    ("cos", Cos),
+    ("tan", Tan),
+    ("log", Log),
+    ("erfc", Erfc),
+    ("erf", Erf),
+    ("beta", Beta),
+    ("min", Min),
+    ("max", Max),
+    ("gcd", Gcd),
+    ("lcm", Lcm),
+    ("nCr", Ncr),
+    ("ncr", Ncr),
+    ("nPr", Npr),
+    ("npr", Npr),
+    ("mod", Mod),
+    ("div", IntegerDivide),
+    ("floor", Floor),
+    ("ceil", Ceil),
+    ("trunc", Trunc),
+    ("abs", Abs),
+    ("sign", Sign),
+    ("sgn", Sign),
+    ("xor", Xor),
+    ("XOR", Xor),
+    ("and", And),
+    ("AND", And),
+    ("not", Not),
+    ("NOT", Not),
+    ("rol", RotateLeft),
+    ("ROL", RotateLeft),
+    ("ror", RotateRight),
+    ("ROR", RotateRight),
+    ("or", Or),
+    ("OR", Or),
+    ("in", In),
+    ("to", In),
+    ("Ans", Ans),
+    ("ans", Ans),
+    ("ln", Ln),
+    ("pi", Pi),
+    ("π", Pi),
+    ("√", Sqrt),
+    ("e", E),
+    ("i", I),
+    ("E", EXP),
+];
+
+//splits text into the items a user would have pressed, paired with where they came from
+pub fn tokenize(text: &str) -> Result<Vec<(Range<usize>, Item)>, ParseError> {
+    let mut tokens: Vec<(Range<usize>, Item)> = vec![];
+    let mut index = 0;
+
+    while let Some(c) = text[index..].chars().next() {
+        let start = index;
+        index += c.len_utf8();
+        let item = match c {
+            _ if c.is_whitespace() => continue,
+            '0' if radix_length(&text[start..]) > 0 => {
+                index = start + radix_length(&text[start..]);
+                let radix = Radix::of(&text[start..index]);
+                let digits = &text[start + radix.prefix().len()..index];
+                Number(format!("{}{}", radix.prefix(), digits.to_uppercase()))
+            }
+            '0'..='9' | '.' => Number(c.into()),
+            '+' => Add,
+            '-' | '–' | '−' => Subtract,
+            '*' | '×' => Multiply,
+            '/' if text[index..].starts_with('/') => {
+                index += 1;
+                IntegerDivide
+            }
+            '/' | '÷' => Divide,
+            '^' => Power,
+            '!' => Factorial,
+            '%' => Percent,
+            '(' => OpeningParenthesis,
+            ')' => ClosingParenthesis,
+            ',' => Comma,
+            '&' => And,
+            '|' => Or,
+            '~' => Not,
+            '<' if text[index..].starts_with('<') => {
+                index += 1;
+                LeftShift
+            }
+            '>' if text[index..].starts_with('>') => {
+                index += 1;
+                RightShift
+            }
+            _ => {
+                let (length, item) = name(text, start)?;
+                index = start + length;
+
+                //functions are rendered as "sin(" so the parenthesis belongs to the name
+                let rest = &text[index..];
+                let trimmed = rest.trim_start();
+                let after_value = tokens
+                    .last()
+                    .is_some_and(|(_, last)| last.can_put_operation_after());
+                if item.is_opening_parenthesis()
+                    && trimmed.starts_with('(')
+                    && !(item == Mod && after_value)
+                {
+                    index += rest.len() - trimmed.len() + 1;
+                    item
+                } else if item == Mod {
+                    //mod without a parenthesis or after a value goes between values, 7 mod (1+2)
+                    Modulo
+                } else if item == Min {
+                    //and min without one is minutes
+                    Unit("min".into())
+                } else {
+                    item
+                }
+            }
+        };
+        tokens.push((start..index, item));
+    }
+
+    Ok(tokens)
+}
+
+//the length of a number like 0xFF or 0b101 at the start of text, 0 if there isn't one
+fn radix_length(text: &str) -> usize {
+    let radix = Radix::of(text);
+    let Some(rest) = text
+        .strip_prefix(radix.prefix())
+        .filter(|_| radix != Radix::Decimal)
+    else {
+        return 0;
+    };
+    match rest.find(|c: char| !c.is_digit(radix.base())) {
+        Some(0) => 0,
+        Some(length) => radix.prefix().len() + length,
+        None if rest.is_empty() => 0,
+        None => text.len(),
+    }
+}
+
+//the length and item of the name at start, words that aren't built in or units are variables
+fn name(text: &str, start: usize) -> Result<(usize, Item), ParseError> {
+    let rest = &text[start..];
+    let word = &rest[..identifier_length(rest)];
+
+    if let Some((_, item)) = NAMES.iter().find(|(name, _)| *name == word) {
+        return Ok((word.len(), item.clone()));
+    }
+    //numbers straight after a name like sin30 or 1E5 aren't part of it
+    let prefix = NAMES.iter().find(|(name, _)| {
+        rest.starts_with(name)
+            && (word.is_empty() || word[name.len()..].chars().all(|c| c.is_ascii_digit()))
+    });
+    if let Some((name, item)) = prefix {
+        return Ok((name.len(), item.clone()));
+    }
+
+    if word.is_empty() {
+        let length = rest
+            .chars()
+            .take_while(|c| c.is_alphabetic())
+            .map(char::len_utf8)
+            .sum::<usize>()
+            .max(rest.chars().next().map_or(0, char::len_utf8));
+        return Err(ParseError::UnknownSymbol(start..start + length));
+    }
+    //f(2) calls a function but f (2) multiplies a variable
+    if text[start + word.len()..].starts_with('(') {
+        return Ok((word.len(), Call(word.into())));
+    }
+    if is_unit(word) {
+        return Ok((word.len(), Unit(word.into())));
+    }
+    Ok((word.len(), Variable(word.into())))
+}
+
+//variable names are ascii letters, digits and underscores that don't start with a digit
+fn identifier_length(text: &str) -> usize {
+    if !text.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        return 0;
+    }
+    text.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(text.len())
+}
+
+fn is_reserved(name: &str) -> bool {
+    NAMES.iter().any(|(reserved, _)| *reserved == name)
+}
+
+pub fn is_variable_name(name: &str) -> bool {
+    !name.is_empty() && identifier_length(name) == name.len() && !is_reserved(name)
+}
+
+//the name on the left of an equals sign
+#[derive(Debug, PartialEq, Clone)]
+pub enum Target {
+    Variable(String),
+    //the name and parameters of a function like f(x, y)
+    Function(String, Vec<String>),
+}
+
+//splits "x = 3.5" or "f(x) = x^2" into what's being named and the byte index the equation starts at
+pub fn split_assignment(text: &str) -> Result<Option<(Target, usize)>, ParseError> {
+    let Some(equals) = text.find('=') else {
+        return Ok(None);
+    };
+    let left = &text[..equals];
+    let start = left.len() - left.trim_start().len();
+    let left = left.trim();
+    let span = start..start + left.len().max(1);
+
+    let target = match left.strip_suffix(')').and_then(|left| left.split_once('(')) {
+        Some((name, parameters)) => {
+            let name = name.trim_end();
+            check_name(name, start..start + name.len().max(1))?;
+            let parameters = parameters
+                .split(',')
+                .map(|parameter| parameter.trim().to_string())
+                .collect::<Vec<_>>();
+            let repeated = parameters
+                .iter()
+                .enumerate()
+                .any(|(index, parameter)| parameters[..index].contains(parameter));
+            if repeated
+                || !parameters
+                    .iter()
+                    .all(|parameter| is_variable_name(parameter))
+            {
+                return Err(ParseError::UnexpectedSymbol(span));
+            }
+            Target::Function(name.into(), parameters)
+        }
+        None => {
+            check_name(left, span)?;
+            Target::Variable(left.into())
+        }
+    };
+    Ok(Some((target, equals + 1)))
+}
+
+fn check_name(name: &str, span: Range<usize>) -> Result<(), ParseError> {
+    if is_reserved(name) {
+        Err(ParseError::ReservedName(span))
+    } else if !is_variable_name(name) {
+        Err(ParseError::UnexpectedSymbol(span))
+    } else {
+        Ok(())
+    }
+}
+
+//removes thousands separators and a trailing equals sign from text copied from elsewhere
+pub fn normalize_pasted(text: &str) -> String {
+    let chars = text
+        .trim()
+        .trim_end_matches('=')
+        .trim_end()
+        .chars()
+        .collect::<Vec<_>>();
+    let mut normalized = String::new();
+    //whether each open parenthesis is a call like gcd(, whose commas split arguments
+    let mut calls = vec![];
+
+    for (index, c) in chars.iter().enumerate() {
+        match c {
+            '(' => calls.push(index > 0 && chars[index - 1].is_alphabetic()),
+            ')' => {
+                calls.pop();
+            }
+            ',' if !calls.contains(&true) && is_thousands_separator(&chars, index) => continue,
+            _ => {}
+        }
+        normalized.push(*c);
+    }
+
+    normalized
+}
+
+fn is_thousands_separator(chars: &[char], index: usize) -> bool {
+    let before = index.checked_sub(1).and_then(|index| chars.get(index));
+    let group = chars.get(index + 1..index + 4);
+    let after = chars.get(index + 4);
+
+    matches!(before, Some(c) if c.is_ascii_digit())
+        && matches!(group, Some(group) if group.iter().all(char::is_ascii_digit))
+        && !matches!(after, Some(c) if c.is_ascii_digit())
+}
@@ -0,0 +1,349 @@
+use std::cell::Cell;
+
+use dashu_int::IBig;
+use serde::{Deserialize, Serialize};
+
+use crate::calculator::Item;
+use crate::decimal::{integer_choose, integer_lcm};
+use crate::solver::{Backend, Fault, SolveError};
+use Item::*;
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum Radix {
+    Hexadecimal,
+    #[default]
+    Decimal,
+    Octal,
+    Binary,
+}
+
+impl Radix {
+    pub const ALL: [Radix; 4] = [
+        Radix::Hexadecimal,
+        Radix::Decimal,
+        Radix::Octal,
+        Radix::Binary,
+    ];
+
+    pub fn base(self) -> u32 {
+        match self {
+            Radix::Hexadecimal => 16,
+            Radix::Decimal => 10,
+            Radix::Octal => 8,
+            Radix::Binary => 2,
+        }
+    }
+
+    //numbers in the equation start with this, so 0xFF is hexadecimal
+    pub fn prefix(self) -> &'static str {
+        match self {
+            Radix::Hexadecimal => "0x",
+            Radix::Decimal => "",
+            Radix::Octal => "0o",
+            Radix::Binary => "0b",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Radix::Hexadecimal => "HEX",
+            Radix::Decimal => "DEC",
+            Radix::Octal => "OCT",
+            Radix::Binary => "BIN",
+        }
+    }
+
+    //the radix of a number like 0xFF or -0b101
+    pub fn of(num: &str) -> Radix {
+        let digits = num.strip_prefix('-').unwrap_or(num);
+        Radix::ALL
+            .into_iter()
+            .find(|radix| radix.base() != 10 && digits.starts_with(radix.prefix()))
+            .unwrap_or(Radix::Decimal)
+    }
+}
+
+//reads 255, 0xFF, 0o377 and 0b11111111, digits past 63 bits wrap like two's complement
+pub fn parse_integer(num: &str) -> Option<i64> {
+    let (negative, digits) = match num.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, num),
+    };
+    let radix = Radix::of(digits);
+    let digits = &digits[radix.prefix().len()..];
+    if digits.starts_with(['+', '-']) {
+        return None;
+    }
+
+    let value = if radix == Radix::Decimal {
+        digits.parse::<i64>().ok()?
+    } else {
+        u64::from_str_radix(digits, radix.base()).ok()? as i64
+    };
+    if negative {
+        value.checked_neg()
+    } else {
+        Some(value)
+    }
+}
+
+//how many bits integers have in programmer mode and whether the top one is a sign
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Word {
+    pub bits: u32,
+    pub signed: bool,
+}
+
+impl Default for Word {
+    fn default() -> Self {
+        Word {
+            bits: 64,
+            signed: true,
+        }
+    }
+}
+
+impl Word {
+    pub const SIZES: [u32; 4] = [8, 16, 32, 64];
+
+    fn mask(self) -> u64 {
+        u64::MAX >> (64 - self.bits)
+    }
+
+    //the number the lowest bits of pattern stand for
+    pub fn value(self, pattern: u64) -> i128 {
+        let pattern = pattern & self.mask();
+        if self.signed && pattern >> (self.bits - 1) == 1 {
+            pattern as i128 - (1 << self.bits)
+        } else {
+            pattern as i128
+        }
+    }
+
+    //the bits that hold value, negative values are two's complement
+    pub fn pattern(self, value: i128) -> u64 {
+        value as u64 & self.mask()
+    }
+}
+
+//decimal numbers are shown as the word's value, the others as its bits
+pub fn format_integer(value: i64, radix: Radix, word: Word) -> String {
+    let prefix = radix.prefix();
+    let pattern = word.pattern(value as i128);
+    match radix {
+        Radix::Hexadecimal => format!("{prefix}{pattern:X}"),
+        Radix::Decimal => word.value(pattern).to_string(),
+        Radix::Octal => format!("{prefix}{pattern:o}"),
+        Radix::Binary => format!("{prefix}{pattern:b}"),
+    }
+}
+
+//solves with integers the size of the word for programmer mode, so 7/2 is 3
+//results that don't fit wrap around and are remembered so they can be shown
+pub struct Integer {
+    word: Word,
+    overflowed: Cell<bool>,
+}
+
+impl Integer {
+    pub fn new(word: Word) -> Self {
+        Integer {
+            word,
+            overflowed: Cell::new(false),
+        }
+    }
+
+    //whether anything wrapped around since this was made
+    pub fn overflowed(&self) -> bool {
+        self.overflowed.get()
+    }
+
+    fn wrap(&self, value: i128) -> i128 {
+        let wrapped = self.word.value(value as u64);
+        if wrapped != value {
+            self.overflowed.set(true);
+        }
+        wrapped
+    }
+
+    //two 64 bit values can multiply past an i128, but the low bits wrapping_mul keeps are still right
+    fn multiply(&self, value1: i128, value2: i128) -> i128 {
+        match value1.checked_mul(value2) {
+            Some(product) => self.wrap(product),
+            None => {
+                self.overflowed.set(true);
+                self.word.value(value1.wrapping_mul(value2) as u64)
+            }
+        }
+    }
+
+    fn bitwise(&self, value: i128, operation: impl Fn(u64) -> u64) -> i128 {
+        self.word.value(operation(self.word.pattern(value)))
+    }
+
+    //squaring wraps at every step so big exponents don't take forever
+    fn power(&self, base: i128, exponent: i128) -> i128 {
+        let mut base = base;
+        let mut exponent = exponent;
+        let mut result = 1;
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = self.multiply(result, base);
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = self.multiply(base, base);
+            }
+        }
+        result
+    }
+}
+
+impl Backend for Integer {
+    type Value = i128;
+
+    //hexadecimal, octal and binary digits are the word's bits, so 0xFF is -1 in 8 bits
+    fn parse(&self, num: &str) -> Option<i128> {
+        let (negative, digits) = match num.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, num),
+        };
+        let radix = Radix::of(digits);
+        let digits = &digits[radix.prefix().len()..];
+        if digits.starts_with(['+', '-']) {
+            return None;
+        }
+
+        let value = if radix == Radix::Decimal {
+            self.wrap(digits.parse().ok()?)
+        } else {
+            let pattern = u64::from_str_radix(digits, radix.base()).ok()?;
+            if pattern & !self.word.mask() != 0 {
+                self.overflowed.set(true);
+            }
+            self.word.value(pattern)
+        };
+        if negative {
+            Some(self.wrap(-value))
+        } else {
+            Some(value)
+        }
+    }
+
+    fn constant(&self, _constant: &Item) -> Result<i128, Fault> {
+        Err(SolveError::Domain)
+    }
+
+    fn operate(&self, operation: &Item, value1: i128, value2: i128) -> Result<i128, Fault> {
+        let bits = self.word.bits;
+        match operation {
+            Add => Ok(self.wrap(value1 + value2)),
+            Subtract => Ok(self.wrap(value1 - value2)),
+            Multiply => Ok(self.multiply(value1, value2)),
+            Divide if value2 == 0 => Err(SolveError::DivisionByZero),
+            Divide => Ok(self.wrap(value1 / value2)),
+            IntegerDivide if value2 == 0 => Err(SolveError::DivisionByZero),
+            //rounds down like mod takes the sign of the divisor
+            IntegerDivide if value1 % value2 != 0 && (value1 < 0) != (value2 < 0) => {
+                Ok(self.wrap(value1 / value2 - 1))
+            }
+            IntegerDivide => Ok(self.wrap(value1 / value2)),
+            Power if value2 < 0 => Err(SolveError::Domain),
+            Power => Ok(self.power(value1, value2)),
+            And => Ok(self.bitwise(value1, |pattern| pattern & self.word.pattern(value2))),
+            Or => Ok(self.bitwise(value1, |pattern| pattern | self.word.pattern(value2))),
+            Xor => Ok(self.bitwise(value1, |pattern| pattern ^ self.word.pattern(value2))),
+            LeftShift | RightShift | RotateLeft | RotateRight if value2 < 0 => {
+                Err(SolveError::Domain)
+            }
+            //bits shifted out are dropped without counting as an overflow
+            LeftShift if value2 >= bits as i128 => Ok(0),
+            LeftShift => Ok(self.bitwise(value1, |pattern| pattern << value2)),
+            //signed words shift in copies of the sign bit
+            RightShift => Ok(value1 >> value2.min(127)),
+            RotateLeft | RotateRight => {
+                let shift = (value2 % bits as i128) as u32;
+                let shift = if *operation == RotateLeft {
+                    shift
+                } else {
+                    (bits - shift) % bits
+                };
+                Ok(self.bitwise(value1, |pattern| {
+                    if shift == 0 {
+                        pattern
+                    } else {
+                        pattern << shift | pattern >> (bits - shift)
+                    }
+                }))
+            }
+            _ => Err(SolveError::UnsupportedOperator),
+        }
+    }
+
+    fn function(&self, function: &Item, value: i128, _degrees: bool) -> Result<i128, Fault> {
+        match function {
+            Not => Ok(self.bitwise(value, |pattern| !pattern)),
+            Floor | Ceil | Trunc => Ok(value),
+            Abs => Ok(self.wrap(value.abs())),
+            Sign => Ok(value.signum()),
+            _ => Err(SolveError::UnsupportedOperator),
+        }
+    }
+
+    fn nary_function(&self, function: &Item, values: Vec<i128>) -> Result<i128, Fault> {
+        match (function, values.as_slice()) {
+            (Min, _) => values.iter().min().copied().ok_or(SolveError::Syntax),
+            (Max, _) => values.iter().max().copied().ok_or(SolveError::Syntax),
+            (Gcd, _) => Ok(self.wrap(values.iter().fold(0, |gcd, value| integer_gcd(gcd, *value)))),
+            //the lcm can be far past an i128, so it's found exactly and only its low bits are kept
+            (Lcm, _) => {
+                let lcm = values.iter().fold(IBig::ONE, |lcm, value| {
+                    integer_lcm(lcm, &IBig::from(*value))
+                });
+                let pattern = u64::try_from(&lcm % IBig::from(1u128 << 64)).unwrap_or_default();
+                if IBig::from(self.word.value(pattern)) != lcm {
+                    self.overflowed.set(true);
+                }
+                Ok(self.word.value(pattern))
+            }
+            (Ncr | Npr, [n, r]) => {
+                let result = integer_choose(&IBig::from(*n), &IBig::from(*r), *function == Npr)?;
+                i128::try_from(result)
+                    .map(|result| self.wrap(result))
+                    .map_err(|_| SolveError::Overflow as Fault)
+            }
+            (Mod, [_, 0]) => Err(SolveError::DivisionByZero),
+            //the sign of the divisor like the other backends
+            (Mod, [value, divisor]) => {
+                let remainder = value % divisor;
+                if remainder != 0 && (remainder < 0) != (*divisor < 0) {
+                    Ok(remainder + divisor)
+                } else {
+                    Ok(remainder)
+                }
+            }
+            _ => Err(SolveError::UnsupportedOperator),
+        }
+    }
+
+    fn factorial(&self, value: i128) -> Result<i128, Fault> {
+        if value < 0 {
+            return Err(SolveError::Domain);
+        }
+        //past this there are more factors of two than bits, so every bit is 0
+        if value > 2 * self.word.bits as i128 {
+            self.overflowed.set(true);
+            return Ok(0);
+        }
+        Ok((2..=value).fold(1, |product, i| self.wrap(product * i)))
+    }
+}
+
+//values of a word are at most 64 bits, so these can't overflow an i128
+fn integer_gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
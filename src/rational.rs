@@ -0,0 +1,218 @@
+use dashu_float::DBig;
+use dashu_int::ops::{BitTest, UnsignedAbs};
+use dashu_int::{IBig, UBig};
+use dashu_ratio::RBig;
+use serde::{Deserialize, Serialize};
+
+use crate::calculator::Item;
+use crate::decimal::{
+    format_decimal, integer_choose, integer_factorial, integer_gcd, integer_lcm, parse_decimal,
+    MAX_EXPONENT,
+};
+use crate::solver::{Backend, Fault, SolveError};
+use Item::*;
+
+//significant digits shown when a fraction is displayed as a decimal
+const DECIMAL_DIGITS: usize = 20;
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum FractionDisplay {
+    #[default]
+    Fraction,
+    Mixed,
+    Decimal,
+}
+
+impl FractionDisplay {
+    pub fn next(self) -> Self {
+        match self {
+            FractionDisplay::Fraction => FractionDisplay::Mixed,
+            FractionDisplay::Mixed => FractionDisplay::Decimal,
+            FractionDisplay::Decimal => FractionDisplay::Fraction,
+        }
+    }
+}
+
+//solves with exact fractions, anything that can't stay exact is an Inexact error
+pub struct Rational;
+
+impl Rational {
+    fn power(&self, base: RBig, exponent: RBig) -> Result<RBig, Fault> {
+        if !exponent.is_int() {
+            return Err(SolveError::Inexact);
+        }
+        if base.is_zero() {
+            return match exponent.numerator().cmp(&IBig::ZERO) {
+                std::cmp::Ordering::Less => Err(SolveError::DivisionByZero),
+                std::cmp::Ordering::Equal => Ok(RBig::ONE),
+                std::cmp::Ordering::Greater => Ok(base),
+            };
+        }
+
+        let exponent_size = exponent.to_f64().value().abs();
+        let magnitude = base.to_f64().value().abs().log10() * exponent_size;
+        //(1/3)^1000000000 is tiny, but its denominator is still far too long to write out
+        let bits = base
+            .numerator()
+            .unsigned_abs()
+            .bit_len()
+            .max(base.denominator().bit_len());
+        let digits = bits as f64 * std::f64::consts::LOG10_2 * exponent_size;
+        if magnitude > MAX_EXPONENT {
+            return Err(SolveError::Overflow);
+        } else if digits > MAX_EXPONENT {
+            return Err(SolveError::Inexact);
+        }
+
+        let n = usize::try_from(exponent.numerator().unsigned_abs())
+            .map_err(|_| SolveError::Overflow as Fault)?;
+        let result = base.pow(n);
+        if *exponent.numerator() < IBig::ZERO {
+            Ok(RBig::ONE / result)
+        } else {
+            Ok(result)
+        }
+    }
+}
+
+impl Backend for Rational {
+    type Value = RBig;
+
+    fn parse(&self, num: &str) -> Option<RBig> {
+        match num.split_once('/') {
+            Some((numerator, denominator)) => {
+                let denominator = self.parse(denominator)?;
+                if denominator.is_zero() {
+                    return None;
+                }
+                Some(self.parse(numerator)? / denominator)
+            }
+            None => parse_decimal(num).map(|value| {
+                let repr = value.repr();
+                let exponent = repr.exponent();
+                let power = UBig::from(10u8).pow(exponent.unsigned_abs());
+                if exponent < 0 {
+                    RBig::from_parts(repr.significand().clone(), power)
+                } else {
+                    RBig::from(repr.significand() * IBig::from(power))
+                }
+            }),
+        }
+    }
+
+    fn constant(&self, _constant: &Item) -> Result<RBig, Fault> {
+        Err(SolveError::Inexact)
+    }
+
+    fn operate(&self, operation: &Item, value1: RBig, value2: RBig) -> Result<RBig, Fault> {
+        match operation {
+            Add => Ok(value1 + value2),
+            Subtract => Ok(value1 - value2),
+            Multiply => Ok(value1 * value2),
+            Divide if value2.is_zero() => Err(SolveError::DivisionByZero),
+            Divide => Ok(value1 / value2),
+            IntegerDivide if value2.is_zero() => Err(SolveError::DivisionByZero),
+            IntegerDivide => Ok(RBig::from((value1 / value2).floor())),
+            Power => self.power(value1, value2),
+            Nroot => Err(SolveError::Inexact),
+            _ => Err(SolveError::UnsupportedOperator),
+        }
+    }
+
+    fn function(&self, function: &Item, value: RBig, _degrees: bool) -> Result<RBig, Fault> {
+        match function {
+            Floor => Ok(RBig::from(value.floor())),
+            Ceil => Ok(RBig::from(value.ceil())),
+            Trunc => Ok(RBig::from(value.trunc())),
+            Abs if value < RBig::ZERO => Ok(-value),
+            Abs => Ok(value),
+            Sign => Ok(value.signum()),
+            _ => Err(SolveError::Inexact),
+        }
+    }
+
+    fn nary_function(&self, function: &Item, values: Vec<RBig>) -> Result<RBig, Fault> {
+        let integers = || -> Result<Vec<IBig>, Fault> {
+            values
+                .iter()
+                .map(|value| {
+                    if value.is_int() {
+                        Ok(value.numerator().clone())
+                    } else {
+                        Err(SolveError::Domain as Fault)
+                    }
+                })
+                .collect()
+        };
+
+        match (function, values.as_slice()) {
+            (Min, _) => values.iter().min().cloned().ok_or(SolveError::Syntax),
+            (Max, _) => values.iter().max().cloned().ok_or(SolveError::Syntax),
+            (Gcd, _) => Ok(RBig::from(integers()?.iter().fold(IBig::ZERO, integer_gcd))),
+            (Lcm, _) => Ok(RBig::from(integers()?.iter().fold(IBig::ONE, integer_lcm))),
+            (Ncr | Npr, _) => {
+                let integers = integers()?;
+                let ordered = *function == Npr;
+                Ok(RBig::from(integer_choose(
+                    &integers[0],
+                    &integers[1],
+                    ordered,
+                )?))
+            }
+            (Round, [value]) => Ok(RBig::from(value.round())),
+            (Round, [value, places]) => {
+                if !places.is_int() {
+                    return Err(SolveError::Domain);
+                }
+                let places = places.to_f64().value().clamp(-MAX_EXPONENT, MAX_EXPONENT);
+                let power = RBig::from(UBig::from(10u8).pow(places.abs() as usize));
+                let scale = if places < 0.0 {
+                    RBig::ONE / power
+                } else {
+                    power
+                };
+                Ok(RBig::from((value * &scale).round()) / scale)
+            }
+            (Mod, [_, divisor]) if divisor.is_zero() => Err(SolveError::DivisionByZero),
+            (Mod, [value, divisor]) => Ok(value - divisor * RBig::from((value / divisor).floor())),
+            _ => Err(SolveError::Inexact),
+        }
+    }
+
+    fn factorial(&self, value: RBig) -> Result<RBig, Fault> {
+        if !value.is_int() {
+            return Err(SolveError::Inexact);
+        }
+        Ok(RBig::from(integer_factorial(value.numerator())?))
+    }
+}
+
+pub fn format_fraction(value: &RBig, display: FractionDisplay) -> String {
+    let numerator = value.numerator();
+    let denominator = value.denominator();
+    if value.is_int() {
+        return numerator.to_string();
+    }
+
+    match display {
+        FractionDisplay::Fraction => format!("{numerator}/{denominator}"),
+        FractionDisplay::Mixed => {
+            let whole = value.trunc();
+            if whole == IBig::ZERO {
+                return format!("{numerator}/{denominator}");
+            }
+            let remainder = numerator.unsigned_abs() % denominator;
+            format!("{whole} {remainder}/{denominator}")
+        }
+        FractionDisplay::Decimal => {
+            let precision = DECIMAL_DIGITS + 10;
+            let numerator = DBig::from(numerator.clone())
+                .with_precision(precision)
+                .value();
+            let denominator = DBig::from(denominator.clone())
+                .with_precision(precision)
+                .value();
+            format_decimal(&(numerator / denominator), DECIMAL_DIGITS)
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use std::path::PathBuf;
+
+use calculator::{ComplexDisplay, Precision, Rates, MAX_DIGITS};
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+
+use crate::cli::{evaluate, read_rates, Session, EXIT_USAGE};
+
+const HELP: &str = "\
+Type an equation to solve it. Ans is the previous answer.
+Type x = 3.5 to save an answer as a variable named x,
+or f(x, y) = x^2 + y to define a function.
+Built in functions include sinh, cosh, tanh, asinh, acosh, atanh, min, max,
+gcd, lcm, nCr, nPr, round(x, places), mod(x, divisor), log(base, x),
+floor, ceil, trunc, abs and sign, and the special functions erf, erfc,
+gamma, lgamma, beta(a, b), digamma, normcdf and norminv.
+7 mod 3 and 7 div 3 (or 7 // 3) give the remainder and the rounded down quotient.
+Numbers can have units like 5 km + 300 m or 60 mph * 2 h, and in or to converts
+them, like 5 km + 300 m in mi. Units include m, km, cm, mm, mi, yd, ft, inch,
+g, kg, mg, lb, oz, tonne, s, ms, min, h, day, week, year, mph, kph, L, mL,
+J, kJ, cal, kcal, W, kW, N, Pa, Hz, K, B, bit, KB, MB and GB.
+Currencies like 100 USD in EUR use the rates in a JSON or CSV file loaded with :rates.
+
+Commands:
+  :deg          Use degrees for trigonometric functions
+  :rad          Use radians for trigonometric functions
+  :digits N     Calculate with decimals accurate to N significant digits
+  :digits off   Go back to regular floating point numbers
+  :exact        Keep results as exact fractions when possible
+  :complex      Calculate with complex numbers, i is the imaginary unit
+  :polar        Print complex results in polar form
+  :rect         Print complex results in rectangular form
+  :history      List previous equations and answers
+  :vars         List variables and functions
+  :unset NAME   Forget a variable or function
+  :rates FILE   Load exchange rates, :rates on its own reloads the file
+  :help         Print this message
+  :quit         Leave (Ctrl+D works too)";
+
+pub fn run(
+    degrees: bool,
+    precision: Precision,
+    complex_display: ComplexDisplay,
+    rates_path: Option<PathBuf>,
+    rates: Rates,
+) -> i32 {
+    let mut editor = match DefaultEditor::new() {
+        Ok(editor) => editor,
+        Err(error) => {
+            eprintln!("error: couldn't start the terminal: {error}");
+            return EXIT_USAGE;
+        }
+    };
+
+    let mut degrees = degrees;
+    let mut precision = precision;
+    let mut complex_display = complex_display;
+    let mut rates_path = rates_path;
+    let mut session = Session {
+        rates,
+        ..Default::default()
+    };
+
+    loop {
+        let prompt = if degrees { "Deg> " } else { "Rad> " };
+        let line = match editor.readline(prompt) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => return 0,
+            Err(error) => {
+                eprintln!("error: {error}");
+                return EXIT_USAGE;
+            }
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(line);
+
+        match line {
+            ":deg" => degrees = true,
+            ":rad" => degrees = false,
+            ":history" => {
+                for (equation, answer) in &session.history {
+                    println!("{} = {answer}", equation.to_text());
+                }
+            }
+            ":vars" => {
+                for (name, value) in &session.variables {
+                    println!("{name} = {value}");
+                }
+                for (name, function) in &session.functions {
+                    println!("{}", function.to_text(name));
+                }
+            }
+            _ if line.starts_with(":unset ") => {
+                let name = line[":unset ".len()..].trim();
+                let removed = session.variables.remove(name).is_some()
+                    | session.functions.remove(name).is_some();
+                if !removed {
+                    eprintln!("error: there's no variable or function named {name}");
+                }
+            }
+            _ if line == ":rates" || line.starts_with(":rates ") => {
+                let path = line[":rates".len()..].trim();
+                if !path.is_empty() {
+                    rates_path = Some(path.into());
+                }
+                match &rates_path {
+                    Some(path) => {
+                        if let Ok(rates) = read_rates(path) {
+                            println!(
+                                "{} rates from {}",
+                                rates.rates.len(),
+                                rates.updated.as_deref().unwrap_or("an unknown time")
+                            );
+                            session.rates = rates;
+                        }
+                    }
+                    None => eprintln!("error: :rates needs a file the first time"),
+                }
+            }
+            ":digits off" => precision = Precision::Float,
+            ":exact" => precision = Precision::Rational,
+            ":complex" => precision = Precision::Complex,
+            ":polar" => complex_display = ComplexDisplay::Polar,
+            ":rect" => complex_display = ComplexDisplay::Rectangular,
+            _ if line.starts_with(":digits ") => match line[":digits ".len()..].trim().parse() {
+                Ok(digits) if (1..=MAX_DIGITS).contains(&digits) => {
+                    precision = Precision::Decimal(digits)
+                }
+                _ => eprintln!(
+                    "error: :digits needs a number of digits from 1 to {MAX_DIGITS} or off"
+                ),
+            },
+            ":help" => println!("{HELP}"),
+            ":quit" | ":q" => return 0,
+            _ if line.starts_with(':') => eprintln!("error: unknown command {line}, try :help"),
+            //errors are already printed, and the session carries on after them
+            _ => {
+                evaluate(line, degrees, precision, complex_display, &mut session).ok();
+            }
+        }
+    }
+}
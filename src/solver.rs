@@ -1,105 +1,585 @@
-use crate::calculator::{Equation, Item};
+use std::collections::BTreeMap;
+use std::fmt;
+
+use statrs::distribution::{ContinuousCDF, Normal};
+use statrs::function::{beta, erf, gamma};
+
+use crate::calculator::{Equation, Functions, Item};
+use crate::currency::Rates;
+use crate::programmer::parse_integer;
+use crate::units::solve_units;
 use Item::*;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SolveError {
+    Syntax(usize),
+    Domain(usize),
+    DivisionByZero(usize),
+    Overflow(usize),
+    UnbalancedParentheses(usize),
+    UnsupportedOperator(usize),
+    //the exact backend can't represent the result
+    Inexact(usize),
+    UnknownVariable(usize),
+    UnknownFunction(usize),
+    ArgumentCount(usize),
+    //like adding m to s, or converting km to kg
+    IncompatibleUnits(usize),
+    //a currency that the rates file doesn't have
+    UnknownCurrency(usize),
+}
+
+impl SolveError {
+    //index of the item in the equation that caused the error
+    pub fn index(&self) -> usize {
+        match self {
+            SolveError::Syntax(index)
+            | SolveError::Domain(index)
+            | SolveError::DivisionByZero(index)
+            | SolveError::Overflow(index)
+            | SolveError::UnbalancedParentheses(index)
+            | SolveError::UnsupportedOperator(index)
+            | SolveError::Inexact(index)
+            | SolveError::UnknownVariable(index)
+            | SolveError::UnknownFunction(index)
+            | SolveError::ArgumentCount(index)
+            | SolveError::IncompatibleUnits(index)
+            | SolveError::UnknownCurrency(index) => *index,
+        }
+    }
+
+    //the same error caused by another item, like the call of the function it happened in
+    pub(crate) fn at(self, index: usize) -> Self {
+        let fault: Fault = match self {
+            SolveError::Syntax(..) => SolveError::Syntax,
+            SolveError::Domain(..) => SolveError::Domain,
+            SolveError::DivisionByZero(..) => SolveError::DivisionByZero,
+            SolveError::Overflow(..) => SolveError::Overflow,
+            SolveError::UnbalancedParentheses(..) => SolveError::UnbalancedParentheses,
+            SolveError::UnsupportedOperator(..) => SolveError::UnsupportedOperator,
+            SolveError::Inexact(..) => SolveError::Inexact,
+            SolveError::UnknownVariable(..) => SolveError::UnknownVariable,
+            SolveError::UnknownFunction(..) => SolveError::UnknownFunction,
+            SolveError::ArgumentCount(..) => SolveError::ArgumentCount,
+            SolveError::IncompatibleUnits(..) => SolveError::IncompatibleUnits,
+            SolveError::UnknownCurrency(..) => SolveError::UnknownCurrency,
+        };
+        fault(index)
+    }
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            SolveError::Syntax(..) => "Syntax error",
+            SolveError::Domain(..) => "Domain error",
+            SolveError::DivisionByZero(..) => "Can't divide by 0",
+            SolveError::Overflow(..) => "Overflow",
+            SolveError::UnbalancedParentheses(..) => "Unbalanced parentheses",
+            SolveError::UnsupportedOperator(..) => "Unsupported operator",
+            SolveError::Inexact(..) => "Not exact",
+            SolveError::UnknownVariable(..) => "Unknown variable",
+            SolveError::UnknownFunction(..) => "Unknown function",
+            SolveError::ArgumentCount(..) => "Wrong number of arguments",
+            SolveError::IncompatibleUnits(..) => "Incompatible units",
+            SolveError::UnknownCurrency(..) => "No exchange rate",
+        };
+        write!(f, "{message}")
+    }
+}
+
+//functions calling themselves forever are an overflow instead of a crash
+const MAX_CALL_DEPTH: usize = 100;
+
+//what Ans, variables, functions and currencies stand for
+#[derive(Clone, Debug, Default)]
+pub struct Memory {
+    pub ans: Stored,
+    pub variables: BTreeMap<String, Stored>,
+    pub functions: Functions,
+    pub rates: Rates,
+}
+
+//Ans or a variable, written so every backend can parse it, quantities keep their unit like mi
+#[derive(Clone, Debug, Default)]
+pub struct Stored {
+    pub number: String,
+    pub unit: Option<Equation>,
+}
+
+//a SolveError that still needs the index of the item that caused it
+pub type Fault = fn(usize) -> SolveError;
+
+//the arithmetic the solver runs an equation with
+pub trait Backend {
+    type Value: Clone;
+
+    fn parse(&self, num: &str) -> Option<Self::Value>;
+
+    //Pi | E | I, and Unit for backends that keep track of units
+    fn constant(&self, constant: &Item) -> Result<Self::Value, Fault>;
+
+    //Add | Subtract | Multiply | Divide | IntegerDivide | Power | Nroot, and the bitwise operators
+    fn operate(
+        &self,
+        operation: &Item,
+        value1: Self::Value,
+        value2: Self::Value,
+    ) -> Result<Self::Value, Fault>;
+
+    //Sin | Ln | Cos | Log | Tan | Sqrt | Asin | Acos | Atan, the hyperbolic functions
+    //the special functions that take one value, Floor | Ceil | Trunc | Abs | Sign and Not
+    fn function(
+        &self,
+        function: &Item,
+        value: Self::Value,
+        degrees: bool,
+    ) -> Result<Self::Value, Fault>;
+
+    //Min | Max | Gcd | Lcm | Ncr | Npr | Round | Mod, and Log with the base first
+    fn nary_function(
+        &self,
+        function: &Item,
+        values: Vec<Self::Value>,
+    ) -> Result<Self::Value, Fault>;
+
+    fn factorial(&self, value: Self::Value) -> Result<Self::Value, Fault>;
+}
+
+//equations with units are checked and converted, 5 km + 300 m in mi is about 3.29
+pub fn solve(equation: &Equation, degrees: bool, ans: f64) -> Result<f64, SolveError> {
+    let memory = Memory {
+        ans: Stored {
+            number: ans.to_string(),
+            unit: None,
+        },
+        ..Default::default()
+    };
+    if equation.has_units(&memory) {
+        solve_units(equation, degrees, &memory).map(|(value, _)| value)
+    } else {
+        solve_with(&Float, equation, degrees, &memory)
+    }
+}
+
 //https://www.geeksforgeeks.org/expression-evaluation/
-pub fn solve(equation: &Equation, degrees: bool, ans: f64) -> Option<f64> {
-    let items = equation.clean(ans);
+pub fn solve_with<B: Backend>(
+    backend: &B,
+    equation: &Equation,
+    degrees: bool,
+    memory: &Memory,
+) -> Result<B::Value, SolveError> {
+    evaluate(backend, equation, degrees, memory, &BTreeMap::new(), 0)
+}
+
+//arguments are the values of the parameters when the equation is the body of a function
+fn evaluate<B: Backend>(
+    backend: &B,
+    equation: &Equation,
+    degrees: bool,
+    memory: &Memory,
+    arguments: &BTreeMap<String, B::Value>,
+    depth: usize,
+) -> Result<B::Value, SolveError> {
+    let items = equation.clean(memory)?;
 
-    if items.len() == 0 {
-        return Some(0.0);
+    if items.is_empty() {
+        return backend.parse("0").ok_or(SolveError::Syntax(0));
     }
 
-    let mut operation_stack = vec![];
+    let mut operation_stack: Vec<(usize, Item)> = vec![];
     let mut value_stack = vec![];
+    //the number of arguments inside each opening parenthesis on the operation stack,
+    //and the number of values there were before it
+    let mut argument_counts: Vec<(usize, usize)> = vec![];
 
-    for item in items {
+    for (index, item) in items {
         match item {
-            Number(num) => value_stack.push(num.parse().ok()?),
-            _ if item.is_opening_parenthesis() => operation_stack.push(item),
+            Number(num) => value_stack.push(backend.parse(&num).ok_or(SolveError::Syntax(index))?),
+            Pi | E | I => value_stack.push(backend.constant(&item).map_err(|fault| fault(index))?),
+            Variable(name) => value_stack.push(
+                arguments
+                    .get(&name)
+                    .cloned()
+                    .ok_or(SolveError::UnknownVariable(index))?,
+            ),
+            //parameters named like a unit hide it
+            Unit(ref name) => value_stack.push(match arguments.get(name) {
+                Some(value) => value.clone(),
+                None => backend.constant(&item).map_err(|fault| fault(index))?,
+            }),
+            _ if item.is_opening_parenthesis() => {
+                operation_stack.push((index, item));
+                argument_counts.push((1, value_stack.len()));
+            }
+            Comma => {
+                while let Some(false) = operation_stack
+                    .last()
+                    .map(|(_, item)| item.is_opening_parenthesis())
+                {
+                    apply_operation(backend, &mut operation_stack, &mut value_stack)?;
+                }
+                match (operation_stack.last(), argument_counts.last_mut()) {
+                    (Some((_, parenthesis)), Some((count, _)))
+                        if *parenthesis.arguments().end() > 1 =>
+                    {
+                        *count += 1
+                    }
+                    _ => return Err(SolveError::Syntax(index)),
+                }
+            }
             ClosingParenthesis => {
                 while let Some(false) = operation_stack
                     .last()
-                    .map(|item| item.is_opening_parenthesis())
+                    .map(|(_, item)| item.is_opening_parenthesis())
                 {
-                    let value2 = value_stack.pop()?;
-                    let value1 = value_stack.pop()?;
-                    let operation = operation_stack.pop()?;
-                    value_stack.push(evaluate(operation, value1, value2));
+                    apply_operation(backend, &mut operation_stack, &mut value_stack)?;
+                }
+                let (parenthesis_index, parenthesis) = operation_stack
+                    .pop()
+                    .ok_or(SolveError::UnbalancedParentheses(index))?;
+                let (count, start) = argument_counts.pop().unwrap_or((1, 0));
+                if value_stack.len() != start + count {
+                    return Err(SolveError::Syntax(parenthesis_index));
+                } else if !parenthesis.arguments().contains(&count) {
+                    return Err(SolveError::ArgumentCount(parenthesis_index));
                 }
-                if let Some(parenthesis) = operation_stack.pop() {
-                    if parenthesis != OpeningParenthesis {
-                        let last = value_stack.last_mut()?;
-                        match parenthesis {
-                            Sin => *last = if degrees { last.to_radians() } else { *last }.sin(),
-                            Cos => *last = if degrees { last.to_radians() } else { *last }.cos(),
-                            Tan => *last = if degrees { last.to_radians() } else { *last }.tan(),
-                            Ln => *last = last.ln(),
-                            Log => *last = last.log10(),
-                            Sqrt => *last = last.sqrt(),
-                            Asin => {
-                                *last = if degrees {
-                                    last.asin().to_degrees()
-                                } else {
-                                    last.asin()
-                                }
-                            }
-                            Acos => {
-                                *last = if degrees {
-                                    last.acos().to_degrees()
-                                } else {
-                                    last.acos()
-                                }
-                            }
-                            Atan => {
-                                *last = if degrees {
-                                    last.atan().to_degrees()
-                                } else {
-                                    last.atan()
-                                }
-                            }
-                            Nroot => todo!(),
-                            _ => {} //OpeningParenthesis | Sin | Ln | Cos | Log | Tan | Sqrt | Asin | Acos | Atan | Nroot
-                        }
+                match parenthesis {
+                    OpeningParenthesis => {}
+                    Call(name) => {
+                        let values = value_stack.split_off(start);
+                        let result = call(backend, &name, values, degrees, memory, depth)
+                            .map_err(|error| error.at(parenthesis_index))?;
+                        value_stack.push(result);
+                    }
+                    Sin | Ln | Cos | Log | Tan | Sqrt | Asin | Acos | Atan | Sinh | Cosh | Tanh
+                    | Asinh | Acosh | Atanh | Erf | Erfc | Gamma | Lgamma | Digamma | NormCdf
+                    | NormInv | Not | Floor | Ceil | Trunc | Abs | Sign
+                        if count == 1 =>
+                    {
+                        let last = value_stack
+                            .pop()
+                            .ok_or(SolveError::Syntax(parenthesis_index))?;
+                        value_stack.push(
+                            backend
+                                .function(&parenthesis, last, degrees)
+                                .map_err(|fault| fault(parenthesis_index))?,
+                        );
+                    }
+                    _ => {
+                        let values = value_stack.split_off(start);
+                        value_stack.push(
+                            backend
+                                .nary_function(&parenthesis, values)
+                                .map_err(|fault| fault(parenthesis_index))?,
+                        );
                     }
                 }
             }
-            Add | Subtract | Multiply | Divide | Power => {
-                while let Some(last_item) = operation_stack.last() {
+            Add | Subtract | Multiply | Divide | Modulo | IntegerDivide | Power | Nroot | And
+            | Or | Xor | LeftShift | RightShift | RotateLeft | RotateRight => {
+                while let Some((_, last_item)) = operation_stack.last() {
                     if last_item.has_precedence_over(&item) && value_stack.len() >= 2 {
-                        let value2 = value_stack.pop()?;
-                        let value1 = value_stack.pop()?;
-                        let operation = operation_stack.pop()?;
-
-                        value_stack.push(evaluate(operation, value1, value2));
+                        apply_operation(backend, &mut operation_stack, &mut value_stack)?;
                     } else {
                         break;
                     }
                 }
-                operation_stack.push(item);
+                operation_stack.push((index, item));
             }
             Factorial => {
-                let last = value_stack.last_mut()?;
-                *last = statrs::function::gamma::gamma(*last + 1.0);
+                let last = value_stack.pop().ok_or(SolveError::Syntax(index))?;
+                value_stack.push(backend.factorial(last).map_err(|fault| fault(index))?);
+            }
+            _ => return Err(SolveError::UnsupportedOperator(index)),
+        }
+    }
+
+    while !operation_stack.is_empty() {
+        apply_operation(backend, &mut operation_stack, &mut value_stack)?;
+    }
+
+    value_stack.pop().ok_or(SolveError::Syntax(0))
+}
+
+//solves the body of a user defined function with its parameters set to the values
+fn call<B: Backend>(
+    backend: &B,
+    name: &str,
+    values: Vec<B::Value>,
+    degrees: bool,
+    memory: &Memory,
+    depth: usize,
+) -> Result<B::Value, SolveError> {
+    let function = memory
+        .functions
+        .get(name)
+        .ok_or(SolveError::UnknownFunction(0))?;
+    if values.len() != function.parameters.len() {
+        return Err(SolveError::ArgumentCount(0));
+    } else if depth >= MAX_CALL_DEPTH {
+        return Err(SolveError::Overflow(0));
+    }
+
+    //parameters hide variables with the same name
+    let mut memory = memory.clone();
+    for parameter in &function.parameters {
+        memory.variables.remove(parameter);
+    }
+    let arguments = function.parameters.iter().cloned().zip(values).collect();
+    evaluate(
+        backend,
+        &function.body,
+        degrees,
+        &memory,
+        &arguments,
+        depth + 1,
+    )
+}
+
+fn apply_operation<B: Backend>(
+    backend: &B,
+    operation_stack: &mut Vec<(usize, Item)>,
+    value_stack: &mut Vec<B::Value>,
+) -> Result<(), SolveError> {
+    let (index, operation) = operation_stack.pop().unwrap();
+    if operation.is_opening_parenthesis() {
+        return Err(SolveError::UnbalancedParentheses(index));
+    }
+    let value2 = value_stack.pop().ok_or(SolveError::Syntax(index))?;
+    let value1 = value_stack.pop().ok_or(SolveError::Syntax(index))?;
+    let result = match operation {
+        //7 mod 3 is mod(7, 3)
+        Modulo => backend.nary_function(&Mod, vec![value1, value2]),
+        _ => backend.operate(&operation, value1, value2),
+    };
+    value_stack.push(result.map_err(|fault| fault(index))?);
+    Ok(())
+}
+
+pub struct Float;
+
+impl Backend for Float {
+    type Value = f64;
+
+    fn parse(&self, num: &str) -> Option<f64> {
+        //exact answers are written as fractions
+        match num.split_once('/') {
+            Some((numerator, denominator)) => {
+                Some(numerator.parse::<f64>().ok()? / denominator.parse::<f64>().ok()?)
             }
-            _ => {}
+            None => num
+                .parse()
+                .ok()
+                .or_else(|| parse_integer(num).map(|value| value as f64)),
+        }
+    }
+
+    fn constant(&self, constant: &Item) -> Result<f64, Fault> {
+        match constant {
+            Pi => Ok(std::f64::consts::PI),
+            E => Ok(std::f64::consts::E),
+            I => Err(SolveError::Domain),
+            _ => Err(SolveError::UnsupportedOperator),
         }
     }
 
-    for operation in operation_stack.into_iter().rev() {
-        let value2 = value_stack.pop()?;
-        let value1 = value_stack.pop()?;
-        value_stack.push(evaluate(operation, value1, value2));
+    fn operate(&self, operation: &Item, value1: f64, value2: f64) -> Result<f64, Fault> {
+        let result = match operation {
+            Add => value1 + value2,
+            Subtract => value1 - value2,
+            Multiply => value1 * value2,
+            Divide if value2 == 0.0 => return Err(SolveError::DivisionByZero),
+            Divide => value1 / value2,
+            IntegerDivide if value2 == 0.0 => return Err(SolveError::DivisionByZero),
+            IntegerDivide => (value1 / value2).floor(),
+            Power if value1 == 0.0 && value2 < 0.0 => return Err(SolveError::DivisionByZero),
+            Power => value1.powf(value2),
+            Nroot if value1 == 0.0 => return Err(SolveError::DivisionByZero),
+            Nroot => nroot(value1, value2),
+            _ => return Err(SolveError::UnsupportedOperator),
+        };
+        check(result)
     }
 
-    value_stack.pop()
+    fn function(&self, function: &Item, value: f64, degrees: bool) -> Result<f64, Fault> {
+        let result = match function {
+            Sin => if degrees { value.to_radians() } else { value }.sin(),
+            Cos => if degrees { value.to_radians() } else { value }.cos(),
+            Tan => if degrees { value.to_radians() } else { value }.tan(),
+            Ln | Log if value <= 0.0 => return Err(SolveError::Domain),
+            Ln => value.ln(),
+            Log => value.log10(),
+            Sqrt => value.sqrt(),
+            Asin => {
+                if degrees {
+                    value.asin().to_degrees()
+                } else {
+                    value.asin()
+                }
+            }
+            Acos => {
+                if degrees {
+                    value.acos().to_degrees()
+                } else {
+                    value.acos()
+                }
+            }
+            Atan => {
+                if degrees {
+                    value.atan().to_degrees()
+                } else {
+                    value.atan()
+                }
+            }
+            //hyperbolic functions don't take angles so degrees don't matter
+            Sinh => value.sinh(),
+            Cosh => value.cosh(),
+            Tanh => value.tanh(),
+            Asinh => value.asinh(),
+            Acosh => value.acosh(),
+            Atanh if value.abs() >= 1.0 => return Err(SolveError::Domain),
+            Atanh => value.atanh(),
+            Erf => erf::erf(value),
+            Erfc => erf::erfc(value),
+            //the poles of gamma and digamma
+            Gamma | Lgamma | Digamma if value <= 0.0 && value.fract() == 0.0 => {
+                return Err(SolveError::Domain)
+            }
+            Gamma => gamma::gamma(value),
+            //ln|Γ(x)| = ln(π) - ln|sin(πx)| - ln(Γ(1 - x)) for negative values
+            Lgamma if value < 0.0 => {
+                std::f64::consts::PI.ln()
+                    - (std::f64::consts::PI * value).sin().abs().ln()
+                    - gamma::ln_gamma(1.0 - value)
+            }
+            Lgamma => gamma::ln_gamma(value),
+            Digamma => gamma::digamma(value),
+            NormCdf => standard_normal().cdf(value),
+            NormInv if value <= 0.0 || value >= 1.0 => return Err(SolveError::Domain),
+            NormInv => standard_normal().inverse_cdf(value),
+            Floor => value.floor(),
+            Ceil => value.ceil(),
+            Trunc => value.trunc(),
+            Abs => value.abs(),
+            Sign if value == 0.0 => 0.0,
+            Sign => value.signum(),
+            _ => return Err(SolveError::UnsupportedOperator),
+        };
+        check(result)
+    }
+
+    fn nary_function(&self, function: &Item, values: Vec<f64>) -> Result<f64, Fault> {
+        let integers = || {
+            if values.iter().all(|value| value.fract() == 0.0) {
+                Ok(values.iter().copied())
+            } else {
+                Err(SolveError::Domain as Fault)
+            }
+        };
+        let result = match (function, values.as_slice()) {
+            (Min, _) => values.iter().copied().fold(f64::INFINITY, f64::min),
+            (Max, _) => values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            (Gcd, _) => integers()?.fold(0.0, gcd),
+            (Lcm, _) => integers()?.fold(1.0, |lcm, value| {
+                if lcm == 0.0 || value == 0.0 {
+                    0.0
+                } else {
+                    (lcm / gcd(lcm, value) * value).abs()
+                }
+            }),
+            (Ncr, [n, r]) => choose(*n, *r, false)?,
+            (Npr, [n, r]) => choose(*n, *r, true)?,
+            (Log, [base, value]) if *base <= 0.0 || *base == 1.0 || *value <= 0.0 => {
+                return Err(SolveError::Domain)
+            }
+            (Log, [base, value]) => value.ln() / base.ln(),
+            (Round, [value]) => value.round(),
+            (Round, [_, places]) if places.fract() != 0.0 => return Err(SolveError::Domain),
+            (Round, [value, places]) => {
+                let scale = 10f64.powf(places.clamp(-400.0, 400.0));
+                if scale == 0.0 {
+                    0.0
+                } else if (value * scale).is_infinite() {
+                    //already rounded to more places than a float has
+                    *value
+                } else {
+                    (value * scale).round() / scale
+                }
+            }
+            (Mod, [_, divisor]) if *divisor == 0.0 => return Err(SolveError::DivisionByZero),
+            (Mod, [value, divisor]) => value - divisor * (value / divisor).floor(),
+            (Beta, [a, b]) => {
+                beta::checked_beta(*a, *b).map_err(|_| SolveError::Domain as Fault)?
+            }
+            _ => return Err(SolveError::UnsupportedOperator),
+        };
+        check(result)
+    }
+
+    fn factorial(&self, value: f64) -> Result<f64, Fault> {
+        if value < 0.0 && value.fract() == 0.0 {
+            return Err(SolveError::Domain);
+        }
+        //gamma is a little off for whole numbers, so 3! would be 5.999999999999988
+        if value.fract() == 0.0 && value <= 170.0 {
+            return Ok((2..=value as u32).fold(1.0, |product, i| product * i as f64));
+        }
+        check(gamma::gamma(value + 1.0))
+    }
+}
+
+fn standard_normal() -> Normal {
+    Normal::new(0.0, 1.0).unwrap()
+}
+
+fn check(value: f64) -> Result<f64, Fault> {
+    if value.is_nan() {
+        Err(SolveError::Domain)
+    } else if value.is_infinite() {
+        Err(SolveError::Overflow)
+    } else {
+        Ok(value)
+    }
+}
+
+fn gcd(a: f64, b: f64) -> f64 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0.0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+//nPr when ordered is true and nCr when it's false
+fn choose(n: f64, r: f64, ordered: bool) -> Result<f64, Fault> {
+    if n.fract() != 0.0 || r.fract() != 0.0 || n < 0.0 || r < 0.0 {
+        return Err(SolveError::Domain);
+    } else if r > n {
+        return Ok(0.0);
+    }
+
+    let terms = if ordered { r } else { r.min(n - r) };
+    let mut result = 1.0;
+    let mut i = 0.0;
+    while i < terms {
+        result *= n - i;
+        if !ordered {
+            result /= i + 1.0;
+        }
+        if result.is_infinite() {
+            return Err(SolveError::Overflow);
+        }
+        i += 1.0;
+    }
+    Ok(result.round())
 }
 
-fn evaluate(operation: Item, value1: f64, value2: f64) -> f64 {
-    match operation {
-        Add => value1 + value2,
-        Subtract => value1 - value2,
-        Multiply => value1 * value2,
-        Divide => value1 / value2,
-        Power => value1.powf(value2),
-        _ => todo!(),
+//value1 is the index and value2 is the radicand, so 3√-8 is nroot(3.0, -8.0)
+fn nroot(index: f64, radicand: f64) -> f64 {
+    if radicand < 0.0 && index.fract() == 0.0 && index % 2.0 != 0.0 {
+        -(-radicand).powf(1.0 / index)
+    } else {
+        radicand.powf(1.0 / index)
     }
 }
@@ -0,0 +1,128 @@
+use std::ops::Range;
+
+use statrs::statistics::{Data, OrderStatistics, Statistics};
+
+use crate::answer::{Answer, Variables};
+use crate::parser::ParseError;
+
+//what the statistics panel shows for a list of numbers
+#[derive(Clone, PartialEq, Debug)]
+pub struct Summary {
+    pub count: usize,
+    pub sum: f64,
+    pub mean: f64,
+    pub median: f64,
+    //every value that appears the most, empty when no value repeats
+    pub modes: Vec<f64>,
+    //the sample variance and standard deviation need at least two values
+    pub variance: Option<f64>,
+    pub std_dev: Option<f64>,
+    pub population_variance: f64,
+    pub population_std_dev: f64,
+    pub lower_quartile: f64,
+    pub upper_quartile: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Summary {
+    pub fn new(values: &[f64]) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+
+        let mut data = Data::new(values.to_vec());
+        let sample = |value: f64| if value.is_nan() { None } else { Some(value) };
+        Some(Summary {
+            count: values.len(),
+            sum: values.iter().sum(),
+            mean: values.mean(),
+            median: data.median(),
+            modes: modes(values),
+            variance: sample(values.variance()),
+            std_dev: sample(values.std_dev()),
+            population_variance: values.population_variance(),
+            population_std_dev: values.population_std_dev(),
+            lower_quartile: data.lower_quartile(),
+            upper_quartile: data.upper_quartile(),
+            min: values.min(),
+            max: values.max(),
+        })
+    }
+
+    //the names equations can use for each result, min and max are taken by the functions
+    pub fn entries(&self) -> Vec<(&'static str, f64)> {
+        let mut entries = vec![
+            ("n", self.count as f64),
+            ("sum", self.sum),
+            ("mean", self.mean),
+            ("median", self.median),
+        ];
+        if let Some(mode) = self.modes.first() {
+            entries.push(("mode", *mode));
+        }
+        if let (Some(variance), Some(std_dev)) = (self.variance, self.std_dev) {
+            entries.push(("var", variance));
+            entries.push(("sd", std_dev));
+        }
+        entries.extend([
+            ("pvar", self.population_variance),
+            ("psd", self.population_std_dev),
+            ("q1", self.lower_quartile),
+            ("q3", self.upper_quartile),
+            ("minx", self.min),
+            ("maxx", self.max),
+        ]);
+        entries
+    }
+
+    pub fn variables(&self) -> Variables {
+        self.entries()
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), Answer::Float(value)))
+            .collect()
+    }
+}
+
+fn modes(values: &[f64]) -> Vec<f64> {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+
+    let mut modes = vec![];
+    let mut most = 1;
+    for run in sorted.chunk_by(|a, b| a == b) {
+        if run.len() > most {
+            most = run.len();
+            modes.clear();
+        }
+        if run.len() == most && most > 1 {
+            modes.push(run[0]);
+        }
+    }
+    modes
+}
+
+//numbers separated by commas, semicolons or whitespace, like 1, 2.5 3
+pub fn parse_values(text: &str) -> Result<Vec<f64>, ParseError> {
+    let mut values = vec![];
+    let mut start = None;
+    for (index, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        let separator = c.is_whitespace() || c == ',' || c == ';';
+        match (start, separator) {
+            (None, false) => start = Some(index),
+            (Some(begin), true) => {
+                values.push(parse_value(&text[begin..index], begin..index)?);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    Ok(values)
+}
+
+fn parse_value(text: &str, span: Range<usize>) -> Result<f64, ParseError> {
+    match text.replace('–', "-").parse::<f64>() {
+        Ok(value) if value.is_finite() => Ok(value),
+        _ => Err(ParseError::UnknownSymbol(span)),
+    }
+}
@@ -0,0 +1,436 @@
+use std::cmp::Ordering;
+
+use dashu_int::ops::UnsignedAbs;
+use dashu_int::{IBig, UBig};
+use dashu_ratio::RBig;
+
+use crate::answer::{memory, Answer, Variables};
+use crate::calculator::{superscript_char, Equation, Functions, Item};
+use crate::decimal::integer_factorial;
+use crate::rational::Rational;
+use crate::solver::{solve_with, Backend, Fault, SolveError};
+use Item::*;
+
+//square factors are found by trial division, so bigger radicands take too long
+const MAX_RADICAND: u64 = 1_000_000_000_000;
+const MAX_POWER: usize = 64;
+
+//coefficient × √radicand × π^pi, the radicand is always square free
+#[derive(Clone, PartialEq, Debug)]
+pub struct Exact {
+    coefficient: RBig,
+    radicand: u64,
+    pi: i32,
+}
+
+impl Exact {
+    fn rational(coefficient: RBig) -> Self {
+        Self {
+            coefficient,
+            radicand: 1,
+            pi: 0,
+        }
+        .normalize()
+    }
+
+    fn integer(num: i64) -> Self {
+        Self::rational(RBig::from(num))
+    }
+
+    //zero only has one form so == works
+    fn normalize(self) -> Self {
+        if self.coefficient.is_zero() {
+            Self {
+                coefficient: RBig::ZERO,
+                radicand: 1,
+                pi: 0,
+            }
+        } else {
+            self
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.coefficient.is_zero()
+    }
+
+    fn as_rational(&self) -> Option<&RBig> {
+        (self.radicand == 1 && self.pi == 0).then_some(&self.coefficient)
+    }
+
+    fn add(self, other: Self) -> Result<Self, Fault> {
+        if self.is_zero() {
+            Ok(other)
+        } else if other.is_zero() {
+            Ok(self)
+        } else if self.radicand == other.radicand && self.pi == other.pi {
+            Ok(Self {
+                coefficient: self.coefficient + other.coefficient,
+                ..self
+            }
+            .normalize())
+        } else {
+            Err(SolveError::Inexact)
+        }
+    }
+
+    fn negate(self) -> Self {
+        Self {
+            coefficient: -self.coefficient,
+            ..self
+        }
+    }
+
+    fn multiply(self, other: Self) -> Result<Self, Fault> {
+        let radicand = self
+            .radicand
+            .checked_mul(other.radicand)
+            .ok_or(SolveError::Inexact as Fault)?;
+        let (outside, inside) = split_square(radicand)?;
+        Ok(Self {
+            coefficient: self.coefficient * other.coefficient * RBig::from(outside),
+            radicand: inside,
+            pi: self.pi + other.pi,
+        }
+        .normalize())
+    }
+
+    //1/√r is √r/r
+    fn reciprocal(self) -> Result<Self, Fault> {
+        if self.is_zero() {
+            return Err(SolveError::DivisionByZero);
+        }
+        Ok(Self {
+            coefficient: RBig::ONE / (self.coefficient * RBig::from(self.radicand)),
+            radicand: self.radicand,
+            pi: -self.pi,
+        })
+    }
+
+    fn sqrt(self) -> Result<Self, Fault> {
+        let coefficient = self.as_rational().ok_or(SolveError::Inexact as Fault)?;
+        if *coefficient < RBig::ZERO {
+            return Err(SolveError::Domain);
+        }
+        //√(p/q) is √(pq)/q
+        let numerator = to_u64(&coefficient.numerator().unsigned_abs())?;
+        let denominator = to_u64(coefficient.denominator())?;
+        let product = numerator
+            .checked_mul(denominator)
+            .ok_or(SolveError::Inexact as Fault)?;
+        let (outside, inside) = split_square(product)?;
+        Ok(Self {
+            coefficient: RBig::from_parts(IBig::from(outside), UBig::from(denominator)),
+            radicand: inside,
+            pi: 0,
+        }
+        .normalize())
+    }
+
+    fn power(self, exponent: Self) -> Result<Self, Fault> {
+        let exponent = exponent.as_rational().ok_or(SolveError::Inexact as Fault)?;
+        //x^(n/2) is √x^n
+        let (base, numerator) = if exponent.is_int() {
+            (self, exponent.numerator().clone())
+        } else if *exponent.denominator() == UBig::from(2u8) {
+            (self.sqrt()?, exponent.numerator().clone())
+        } else {
+            return Err(SolveError::Inexact);
+        };
+
+        let negative = numerator < IBig::ZERO;
+        let n =
+            usize::try_from(numerator.unsigned_abs()).map_err(|_| SolveError::Inexact as Fault)?;
+        if n > MAX_POWER {
+            return Err(SolveError::Inexact);
+        }
+        let mut result = Self::integer(1);
+        for _ in 0..n {
+            result = result.multiply(base.clone())?;
+        }
+        if negative {
+            result.reciprocal()
+        } else {
+            Ok(result)
+        }
+    }
+
+    //the angle in degrees when it's a rational number of them
+    fn to_degrees(&self, degrees: bool) -> Option<RBig> {
+        if self.is_zero() {
+            return Some(RBig::ZERO);
+        }
+        match (degrees, self.radicand, self.pi) {
+            (true, 1, 0) => Some(self.coefficient.clone()),
+            (false, 1, 1) => Some(&self.coefficient * RBig::from(180)),
+            _ => None,
+        }
+    }
+
+    fn from_degrees(angle: i64, degrees: bool) -> Self {
+        if degrees {
+            Self::integer(angle)
+        } else {
+            Self {
+                coefficient: RBig::from_parts(IBig::from(angle), UBig::from(180u8)),
+                radicand: 1,
+                pi: 1,
+            }
+            .normalize()
+        }
+    }
+}
+
+//n = outside² × inside
+fn split_square(n: u64) -> Result<(u64, u64), Fault> {
+    if n > MAX_RADICAND {
+        return Err(SolveError::Inexact);
+    }
+    let mut outside = 1;
+    let mut inside = n;
+    let mut factor = 2;
+    while factor * factor <= inside {
+        while inside.is_multiple_of(factor * factor) {
+            inside /= factor * factor;
+            outside *= factor;
+        }
+        factor += 1;
+    }
+    Ok((outside, inside))
+}
+
+fn to_u64(value: &UBig) -> Result<u64, Fault> {
+    u64::try_from(value).map_err(|_| SolveError::Inexact as Fault)
+}
+
+//sin of a whole number of degrees, for the angles that have a simple exact value
+fn exact_sin(angle: i64) -> Option<Exact> {
+    let angle = angle.rem_euclid(360);
+    if angle >= 180 {
+        return exact_sin(angle - 180).map(Exact::negate);
+    }
+    let half = RBig::from_parts(IBig::ONE, UBig::from(2u8));
+    let value = match angle.min(180 - angle) {
+        0 => Exact::integer(0),
+        30 => Exact::rational(half),
+        45 => Exact {
+            coefficient: half,
+            radicand: 2,
+            pi: 0,
+        },
+        60 => Exact {
+            coefficient: half,
+            radicand: 3,
+            pi: 0,
+        },
+        90 => Exact::integer(1),
+        _ => return None,
+    };
+    Some(value)
+}
+
+//a quarter turn on from sin, turned into a single turn first so big angles can't overflow
+fn exact_cos(angle: i64) -> Option<Exact> {
+    exact_sin(angle.rem_euclid(360) + 90)
+}
+
+fn exact_tan(angle: i64) -> Result<Exact, Fault> {
+    let sin = exact_sin(angle).ok_or(SolveError::Inexact as Fault)?;
+    let cos = exact_cos(angle).ok_or(SolveError::Inexact as Fault)?;
+    if cos.is_zero() {
+        return Err(SolveError::Domain);
+    }
+    sin.multiply(cos.reciprocal()?)
+}
+
+//the whole number of degrees in the range of an inverse function that gives the value
+fn inverse(value: &Exact, range: &[i64], function: fn(i64) -> Option<Exact>) -> Option<i64> {
+    range
+        .iter()
+        .copied()
+        .find(|angle| function(*angle).as_ref() == Some(value))
+}
+
+const ASIN_RANGE: &[i64] = &[-90, -60, -45, -30, 0, 30, 45, 60, 90];
+const ATAN_RANGE: &[i64] = &[-60, -45, -30, 0, 30, 45, 60];
+
+//keeps closed forms like √2/2 and π/6 exact while it can
+pub struct Symbolic;
+
+impl Backend for Symbolic {
+    type Value = Exact;
+
+    fn parse(&self, num: &str) -> Option<Exact> {
+        Rational.parse(num).map(Exact::rational)
+    }
+
+    fn constant(&self, constant: &Item) -> Result<Exact, Fault> {
+        match constant {
+            Pi => Ok(Exact {
+                coefficient: RBig::ONE,
+                radicand: 1,
+                pi: 1,
+            }),
+            _ => Err(SolveError::Inexact),
+        }
+    }
+
+    fn operate(&self, operation: &Item, value1: Exact, value2: Exact) -> Result<Exact, Fault> {
+        match operation {
+            Add => value1.add(value2),
+            Subtract => value1.add(value2.negate()),
+            Multiply => value1.multiply(value2),
+            Divide => value1.multiply(value2.reciprocal()?),
+            Power => value1.power(value2),
+            Nroot if value1 == Exact::integer(2) => value2.sqrt(),
+            IntegerDivide => match (value1.as_rational(), value2.as_rational()) {
+                (Some(value1), Some(value2)) => Rational
+                    .operate(operation, value1.clone(), value2.clone())
+                    .map(Exact::rational),
+                _ => Err(SolveError::Inexact),
+            },
+            _ => Err(SolveError::Inexact),
+        }
+    }
+
+    fn function(&self, function: &Item, value: Exact, degrees: bool) -> Result<Exact, Fault> {
+        let angle = || -> Result<i64, Fault> {
+            let angle = value
+                .to_degrees(degrees)
+                .ok_or(SolveError::Inexact as Fault)?;
+            if !angle.is_int() {
+                return Err(SolveError::Inexact);
+            }
+            i64::try_from(angle.numerator().clone()).map_err(|_| SolveError::Inexact as Fault)
+        };
+        match function {
+            Sin => exact_sin(angle()?).ok_or(SolveError::Inexact),
+            Cos => exact_cos(angle()?).ok_or(SolveError::Inexact),
+            Tan => exact_tan(angle()?),
+            Asin => inverse(&value, ASIN_RANGE, exact_sin)
+                .map(|angle| Exact::from_degrees(angle, degrees))
+                .ok_or(SolveError::Inexact),
+            Acos => inverse(&value, ASIN_RANGE, exact_sin)
+                .map(|angle| Exact::from_degrees(90 - angle, degrees))
+                .ok_or(SolveError::Inexact),
+            Atan => inverse(&value, ATAN_RANGE, |angle| exact_tan(angle).ok())
+                .map(|angle| Exact::from_degrees(angle, degrees))
+                .ok_or(SolveError::Inexact),
+            Sqrt => value.sqrt(),
+            Ln if value == Exact::integer(1) => Ok(Exact::integer(0)),
+            Log => {
+                //log(10^n) is n
+                let coefficient = value.as_rational().ok_or(SolveError::Inexact as Fault)?;
+                if *coefficient <= RBig::ZERO {
+                    return Err(SolveError::Domain);
+                }
+                let (power, ten) = match coefficient.cmp(&RBig::ONE) {
+                    Ordering::Less => (RBig::ONE / coefficient, -1),
+                    _ => (coefficient.clone(), 1),
+                };
+                let mut n = 0;
+                let mut remaining = power;
+                while remaining != RBig::ONE {
+                    remaining /= RBig::from(10);
+                    if !remaining.is_int() {
+                        return Err(SolveError::Inexact);
+                    }
+                    n += ten;
+                }
+                Ok(Exact::integer(n))
+            }
+            Floor | Ceil | Trunc | Abs | Sign => {
+                let value = value.as_rational().ok_or(SolveError::Inexact as Fault)?;
+                Rational
+                    .function(function, value.clone(), degrees)
+                    .map(Exact::rational)
+            }
+            _ => Err(SolveError::Inexact),
+        }
+    }
+
+    fn nary_function(&self, function: &Item, values: Vec<Exact>) -> Result<Exact, Fault> {
+        let values = values
+            .iter()
+            .map(|value| value.as_rational().cloned())
+            .collect::<Option<Vec<_>>>()
+            .ok_or(SolveError::Inexact as Fault)?;
+        Rational
+            .nary_function(function, values)
+            .map(Exact::rational)
+    }
+
+    fn factorial(&self, value: Exact) -> Result<Exact, Fault> {
+        let value = value.as_rational().ok_or(SolveError::Inexact as Fault)?;
+        if !value.is_int() {
+            return Err(SolveError::Inexact);
+        }
+        Ok(Exact::rational(RBig::from(integer_factorial(
+            value.numerator(),
+        )?)))
+    }
+}
+
+//the exact form of the equation's answer, when it's worth showing next to the decimal
+pub fn exact_form(
+    equation: &Equation,
+    degrees: bool,
+    ans: Option<&Answer>,
+    variables: &Variables,
+    functions: &Functions,
+) -> Option<String> {
+    let memory = memory(ans, variables, functions);
+    let exact = solve_with(&Symbolic, equation, degrees, &memory).ok()?;
+
+    //terminating decimals are already exact
+    let mut denominator = exact.coefficient.denominator().clone();
+    for factor in [2u8, 5] {
+        while (&denominator % UBig::from(factor)) == UBig::ZERO {
+            denominator /= UBig::from(factor);
+        }
+    }
+    if exact.radicand == 1 && exact.pi == 0 && denominator == UBig::ONE {
+        return None;
+    }
+    Some(format_exact(&exact))
+}
+
+pub fn format_exact(exact: &Exact) -> String {
+    let numerator = exact.coefficient.numerator();
+    let denominator = exact.coefficient.denominator();
+
+    let pi = |power: i32| match power {
+        1 => "π".to_string(),
+        _ => format!(
+            "π{}",
+            power
+                .to_string()
+                .chars()
+                .map(superscript_char)
+                .collect::<String>()
+        ),
+    };
+
+    let mut top = String::new();
+    if *numerator < IBig::ZERO {
+        top.push('-');
+    }
+    let magnitude = numerator.unsigned_abs();
+    if magnitude != UBig::ONE || (exact.radicand == 1 && exact.pi <= 0) {
+        top.push_str(&magnitude.to_string());
+    }
+    if exact.radicand != 1 {
+        top.push_str(&format!("√{}", exact.radicand));
+    }
+    if exact.pi > 0 {
+        top.push_str(&pi(exact.pi));
+    }
+
+    let bottom = match (*denominator == UBig::ONE, exact.pi < 0) {
+        (true, false) => return top,
+        (false, false) => denominator.to_string(),
+        (true, true) => pi(-exact.pi),
+        (false, true) => format!("({denominator}{})", pi(-exact.pi)),
+    };
+    format!("{top}/{bottom}")
+}
@@ -1,12 +1,25 @@
-#[cfg(test)]
-use crate::calculator::Equation;
+use std::collections::BTreeMap;
+
+use num_complex::Complex64;
+
+use crate::answer::{parse_saved, solve_answer, Answer, AnswerFormat, Precision, Variables};
 use crate::calculator::Item::*;
+#[cfg(test)]
+use crate::calculator::{format_number, Equation, Function, Functions};
+use crate::complex::ComplexDisplay;
+use crate::currency::{format_time, parse_csv, parse_json, Rates, RatesError};
+use crate::distribution::{Distribution, Query};
 use crate::equation;
-use crate::solver::solve;
+use crate::parser::{normalize_pasted, ParseError, Target};
+use crate::programmer::{format_integer, parse_integer, Radix, Word};
+use crate::rational::FractionDisplay;
+use crate::solver::{solve, SolveError};
+use crate::statistics::{parse_values, Summary};
+use crate::symbolic::exact_form;
 #[test]
 fn add() {
     let equation = equation![Number("1".into()), Add, Number("2".into())];
-    assert_eq!(solve(&equation, true, 0.0), Some(3.0));
+    assert_eq!(solve(&equation, true, 0.0), Ok(3.0));
 }
 
 #[test]
@@ -20,7 +33,7 @@ fn order_of_operations1() {
         Subtract,
         Number("2".into())
     ];
-    assert_eq!(solve(&equation, true, 0.0), Some(9.0));
+    assert_eq!(solve(&equation, true, 0.0), Ok(9.0));
 }
 
 #[test]
@@ -31,7 +44,7 @@ fn parentheses1() {
         Add,
         Number("2".into())
     ];
-    assert_eq!(solve(&equation, true, 0.0), Some(3.0));
+    assert_eq!(solve(&equation, true, 0.0), Ok(3.0));
 }
 
 #[test]
@@ -44,7 +57,7 @@ fn parentheses2() {
         Multiply,
         Number("3".into())
     ];
-    assert_eq!(solve(&equation, true, 0.0), Some(7.0));
+    assert_eq!(solve(&equation, true, 0.0), Ok(7.0));
 }
 
 #[test]
@@ -64,7 +77,7 @@ fn parentheses3() {
         Add,
         Number("1".into())
     ];
-    assert_eq!(solve(&equation, true, 0.0), Some(19.0));
+    assert_eq!(solve(&equation, true, 0.0), Ok(19.0));
 }
 
 #[test]
@@ -82,7 +95,816 @@ fn parentheses4() {
         Number("2".into()),
         ClosingParenthesis
     ];
-    assert_eq!(solve(&equation, true, 0.0), Some(5.0));
+    assert_eq!(solve(&equation, true, 0.0), Ok(5.0));
+}
+
+#[test]
+fn nroot1() {
+    let equation = equation![Number("3".into()), Nroot, Number("8".into())];
+    assert_eq!(solve(&equation, true, 0.0), Ok(2.0));
+}
+
+#[test]
+fn nroot2() {
+    let equation = equation![
+        Number("3".into()),
+        Nroot,
+        Subtract,
+        Number("8".into()),
+        Add,
+        Number("1".into())
+    ];
+    assert_eq!(solve(&equation, true, 0.0), Ok(-1.0));
+}
+
+#[test]
+fn nroot3() {
+    let equation = equation![Number("2".into()), Nroot, Subtract, Number("4".into())];
+    assert_eq!(solve(&equation, true, 0.0), Err(SolveError::Domain(1)));
+}
+
+#[test]
+fn nroot4() {
+    //a root needs an index before it, so it can't be typed first
+    let mut equation = Equation::new();
+    assert!(!equation.try_push(Nroot));
+    //but history saved with one still doesn't solve
+    let equation: Equation = ron::from_str(r#"(list: [Nroot, Number("8")])"#).unwrap();
+    assert_eq!(solve(&equation, true, 0.0), Err(SolveError::Syntax(0)));
+}
+
+#[test]
+fn division_by_zero() {
+    let equation = equation![
+        Number("1".into()),
+        Add,
+        Number("1".into()),
+        Divide,
+        Number("0".into())
+    ];
+    assert_eq!(
+        solve(&equation, true, 0.0),
+        Err(SolveError::DivisionByZero(3))
+    );
+}
+
+#[test]
+fn domain_error() {
+    let equation = equation![Number("2".into()), Multiply, Ln, Number("0".into())];
+    assert_eq!(solve(&equation, true, 0.0), Err(SolveError::Domain(2)));
+}
+
+#[test]
+fn overflow() {
+    let equation = equation![Number("10".into()), Power, Number("400".into())];
+    assert_eq!(solve(&equation, true, 0.0), Err(SolveError::Overflow(1)));
+}
+
+#[test]
+fn factorial_of_negative_integer() {
+    let equation = equation![
+        OpeningParenthesis,
+        Subtract,
+        Number("2".into()),
+        ClosingParenthesis,
+        Factorial
+    ];
+    assert_eq!(solve(&equation, true, 0.0), Err(SolveError::Domain(3)));
+}
+
+#[test]
+fn factorial_and_percent_of_groups() {
+    let solve_text = |text: &str| solve(&Equation::parse(text).unwrap(), true, 0.0);
+    assert_eq!(solve_text("(1+2)!"), Ok(6.0));
+    assert_eq!(solve_text("(1+2)%"), Ok(0.03));
+    assert_eq!(solve_text("sin(30)!"), solve_text("0.5!"));
+    assert_eq!(solve_text("2(1+2)!"), Ok(12.0));
+    assert_eq!(solve_text("(2+3)!%"), Ok(1.2));
+}
+
+#[test]
+fn trailing_operator() {
+    let equation = equation![Number("2".into()), Add];
+    assert_eq!(solve(&equation, true, 0.0), Err(SolveError::Syntax(1)));
+}
+
+#[test]
+fn parse1() {
+    let equation = equation![
+        Number("2".into()),
+        Sin,
+        Number("3".into()),
+        Number("0".into()),
+        ClosingParenthesis,
+        Add,
+        Number("3".into()),
+        Factorial,
+        Power,
+        Number("2".into()),
+        Subtract,
+        Number("4".into()),
+        Percent
+    ];
+    assert_eq!(Equation::parse("2sin(30)+3!^2 - 4%"), Ok(equation));
+}
+
+#[test]
+fn parse2() {
+    let equation = equation![
+        Pi,
+        Multiply,
+        E,
+        Add,
+        Ans,
+        Subtract,
+        Number("1".into()),
+        Number(".".into()),
+        Number("5".into()),
+        EXP,
+        Subtract,
+        Number("3".into()),
+        Add,
+        Sqrt,
+        Number("4".into()),
+        ClosingParenthesis,
+        Add,
+        Asin,
+        Number("1".into())
+    ];
+    assert_eq!(
+        Equation::parse("π × e + Ans – 1.5E-3 + √(4) + arcsin 1"),
+        Ok(equation)
+    );
+}
+
+#[test]
+fn parse_solve() {
+    let equation = Equation::parse("3 root 8 + (1 + 2) × 3").unwrap();
+    assert_eq!(solve(&equation, true, 0.0), Ok(11.0));
+}
+
+#[test]
+fn parse_unknown_symbol() {
+    assert_eq!(
+        Equation::parse("2 + ¤(3)"),
+        Err(ParseError::UnknownSymbol(4..6))
+    );
+    assert_eq!(
+        Equation::parse("2 # 3"),
+        Err(ParseError::UnknownSymbol(2..3))
+    );
+}
+
+#[test]
+fn parse_unexpected_symbol() {
+    assert_eq!(
+        Equation::parse("√(2))"),
+        Err(ParseError::UnexpectedSymbol(6..7))
+    );
+    assert_eq!(
+        Equation::parse("1E+2"),
+        Err(ParseError::UnexpectedSymbol(2..3))
+    );
+}
+
+#[test]
+fn paste1() {
+    let text = normalize_pasted(" 12,345.6 × 7 = ");
+    assert_eq!(text, "12345.6 × 7");
+    let equation = Equation::parse(&text).unwrap();
+    assert_eq!(solve(&equation, true, 0.0), Ok(86419.2));
+}
+
+#[test]
+fn paste2() {
+    assert_eq!(normalize_pasted("1,000,000 − 2,5"), "1000000 − 2,5");
+    assert_eq!(normalize_pasted("1,0000"), "1,0000");
+    //commas in calls split arguments, even when they look like thousands separators
+    assert_eq!(normalize_pasted("gcd(12,345)"), "gcd(12,345)");
+    assert_eq!(
+        normalize_pasted("max(1,234) + (1,234)"),
+        "max(1,234) + (1234)"
+    );
+    assert_eq!(
+        solve(
+            &Equation::parse(&normalize_pasted("max(1,234)")).unwrap(),
+            true,
+            0.0
+        ),
+        Ok(234.0)
+    );
+}
+
+#[test]
+fn paste_rejected() {
+    let mut equation = equation![Number("2".into()), Add];
+    assert_eq!(
+        equation.try_push_text("3)"),
+        Err(ParseError::UnexpectedSymbol(1..2))
+    );
+    assert_eq!(equation, equation![Number("2".into()), Add]);
+}
+
+#[test]
+fn copy_ascii() {
+    let text = "2sin(30)+3!^2-4%*pi/(1.5E-3-e)+3 root 8+sqrt(Ans)";
+    let equation = Equation::parse(text).unwrap();
+    assert_eq!(equation.to_ascii(), text);
+    assert_eq!(Equation::parse(&equation.to_ascii()), Ok(equation));
+}
+
+#[test]
+fn copy_text() {
+    let equation = Equation::parse("2sin(30 + 12 root 8 - 3^2").unwrap();
+    assert_eq!(equation.to_text(), "2sin(30 + ¹²√8 – 3^2)");
+}
+
+#[test]
+fn copy_latex() {
+    let equation = Equation::parse("2^(1+2)×3 - √(4) + 3 root 8 + sin(π)^2").unwrap();
+    assert_eq!(
+        equation.to_latex(),
+        r"2^{\left(1 + 2\right)} \times 3 - \sqrt{4} + \sqrt[3]{8} + \sin\left(\pi \right)^{2}"
+    );
+}
+
+#[test]
+fn format_number1() {
+    assert_eq!(format_number(341.5), "341.5");
+    assert_eq!(format_number(-2.0), "-2");
+    assert_eq!(format_number(1234567890123456.0), "1.2345679e+15");
+}
+
+//what an equation is solved with besides its text, angles are always in degrees
+#[derive(Default)]
+struct Context<'a> {
+    ans: Option<&'a Answer>,
+    variables: Variables,
+    functions: Functions,
+    rates: Rates,
+    format: AnswerFormat,
+}
+
+fn solve_text(
+    text: &str,
+    precision: Precision,
+    context: Option<&Context>,
+) -> Result<String, SolveError> {
+    let default = Context::default();
+    let context = context.unwrap_or(&default);
+    solve_answer(
+        &Equation::parse(text).unwrap(),
+        true,
+        context.ans,
+        &context.variables,
+        &context.functions,
+        &context.rates,
+        precision,
+    )
+    .map(|answer| answer.format(context.format))
+}
+
+#[test]
+fn decimal1() {
+    assert_eq!(
+        solve_text("0.1+0.2", Precision::Decimal(30), None).unwrap(),
+        "0.3"
+    );
+    assert_eq!(
+        solve_text("1/3", Precision::Decimal(20), None).unwrap(),
+        "0.33333333333333333333"
+    );
+    assert_eq!(
+        solve_text("2/3", Precision::Decimal(5), None).unwrap(),
+        "0.66667"
+    );
+}
+
+#[test]
+fn decimal2() {
+    assert_eq!(
+        solve_text("pi", Precision::Decimal(50), None).unwrap(),
+        "3.1415926535897932384626433832795028841971693993751"
+    );
+    assert_eq!(
+        solve_text("e", Precision::Decimal(20), None).unwrap(),
+        "2.7182818284590452354"
+    );
+    assert_eq!(
+        solve_text("sqrt(2)", Precision::Decimal(25), None).unwrap(),
+        "1.414213562373095048801689"
+    );
+}
+
+#[test]
+fn decimal_functions() {
+    assert_eq!(
+        solve_text("sin(30)", Precision::Decimal(40), None).unwrap(),
+        "0.5"
+    );
+    assert_eq!(
+        solve_text("cos(180)", Precision::Decimal(40), None).unwrap(),
+        "-1"
+    );
+    assert_eq!(
+        solve_text("sin(360)", Precision::Decimal(40), None).unwrap(),
+        "0"
+    );
+    assert_eq!(
+        solve_text("atan(1)", Precision::Decimal(40), None).unwrap(),
+        "45"
+    );
+    assert_eq!(
+        solve_text("asin(0.5)", Precision::Decimal(40), None).unwrap(),
+        "30"
+    );
+    assert_eq!(
+        solve_text("log(1000)", Precision::Decimal(40), None).unwrap(),
+        "3"
+    );
+    assert_eq!(
+        solve_text("3 root -8", Precision::Decimal(40), None).unwrap(),
+        "-2"
+    );
+    assert_eq!(
+        solve_text("25!", Precision::Decimal(40), None).unwrap(),
+        "15511210043330985984000000"
+    );
+    assert_eq!(
+        solve_text("2^100", Precision::Decimal(10), None).unwrap(),
+        "1.2676506e+30"
+    );
+}
+
+#[test]
+fn decimal_big_angles() {
+    //10^40 is 280 degrees past a whole number of turns
+    assert_eq!(
+        solve_text("sin(10^40)", Precision::Decimal(20), None),
+        Ok("-0.98480775301220805937".into())
+    );
+    assert_eq!(
+        solve_text("cos(10^40)", Precision::Decimal(20), None),
+        Ok("0.17364817766693034885".into())
+    );
+    for text in ["sin(3^100)", "cos(sinh(548))"] {
+        let value: f64 = solve_text(text, Precision::Decimal(20), None)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(value.abs() <= 1.0, "{text}");
+    }
+    assert_eq!(
+        solve_text("sin(10^2000)", Precision::Decimal(20), None),
+        Err(SolveError::Overflow(0))
+    );
+}
+
+#[test]
+fn decimal_errors() {
+    assert_eq!(
+        solve_text("tan(90)", Precision::Decimal(30), None),
+        Err(SolveError::Domain(0))
+    );
+    assert_eq!(
+        solve_text("1/(2-2)", Precision::Decimal(30), None),
+        Err(SolveError::DivisionByZero(1))
+    );
+}
+
+#[test]
+fn decimal_ans() {
+    let ans = solve_answer(
+        &Equation::parse("1/3").unwrap(),
+        true,
+        None,
+        &Variables::new(),
+        &Functions::new(),
+        &Rates::default(),
+        Precision::Decimal(30),
+    )
+    .unwrap();
+    let context = Context {
+        ans: Some(&ans),
+        ..Default::default()
+    };
+    assert_eq!(
+        solve_text("Ans×3", Precision::Decimal(30), Some(&context)),
+        Ok("1".into())
+    );
+    assert_eq!(
+        solve_text("Ans×3", Precision::Float, Some(&context)),
+        Ok("1".into())
+    );
+}
+
+#[test]
+fn rational1() {
+    assert_eq!(
+        solve_text("1/3+1/6", Precision::Rational, None).unwrap(),
+        "1/2"
+    );
+    assert_eq!(
+        solve_text("0.1+0.2", Precision::Rational, None).unwrap(),
+        "3/10"
+    );
+    assert_eq!(
+        solve_text("(2/3)^-2", Precision::Rational, None).unwrap(),
+        "9/4"
+    );
+    assert_eq!(solve_text("25%", Precision::Rational, None).unwrap(), "1/4");
+    assert_eq!(solve_text("6/3", Precision::Rational, None).unwrap(), "2");
+}
+
+#[test]
+fn rational_huge_power() {
+    //too many digits to be exact, so these fall back to floats instead of running out of memory
+    assert_eq!(
+        solve_text("(1/3)^1000000000", Precision::Rational, None).unwrap(),
+        "0"
+    );
+    assert_eq!(
+        solve_text("0.5^100000000", Precision::Rational, None).unwrap(),
+        "0"
+    );
+    assert_eq!(
+        solve_text("0.5^-100000000", Precision::Rational, None),
+        Err(SolveError::Overflow(1))
+    );
+}
+
+#[test]
+fn rational_display() {
+    let display = |fraction| Context {
+        format: AnswerFormat {
+            fraction,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mixed = display(FractionDisplay::Mixed);
+    let decimal = display(FractionDisplay::Decimal);
+    assert_eq!(
+        solve_text("-7/3", Precision::Rational, Some(&mixed)),
+        Ok("-2 1/3".into())
+    );
+    assert_eq!(
+        solve_text("2/3", Precision::Rational, Some(&mixed)),
+        Ok("2/3".into())
+    );
+    assert_eq!(
+        solve_text("1/8", Precision::Rational, Some(&decimal)),
+        Ok("0.125".into())
+    );
+    assert_eq!(
+        solve_text("1/3", Precision::Rational, Some(&decimal)),
+        Ok("0.33333333333333333333".into())
+    );
+}
+
+#[test]
+fn rational_inexact() {
+    let equation = Equation::parse("sqrt(4)+1/2").unwrap();
+    assert_eq!(
+        solve_answer(
+            &equation,
+            true,
+            None,
+            &Variables::new(),
+            &Functions::new(),
+            &Rates::default(),
+            Precision::Rational
+        ),
+        Ok(Answer::Float(2.5))
+    );
+    let ans = solve_answer(
+        &Equation::parse("1/3").unwrap(),
+        true,
+        None,
+        &Variables::new(),
+        &Functions::new(),
+        &Rates::default(),
+        Precision::Rational,
+    )
+    .unwrap();
+    let context = Context {
+        ans: Some(&ans),
+        ..Default::default()
+    };
+    assert_eq!(
+        solve_text("Ans×3", Precision::Rational, Some(&context)),
+        Ok("1".into())
+    );
+    assert_eq!(
+        solve_text("Ans×3", Precision::Float, Some(&context)),
+        Ok("1".into())
+    );
+}
+
+#[test]
+fn complex1() {
+    assert_eq!(
+        solve_text("√(-4)", Precision::Complex, None),
+        Ok("2i".into())
+    );
+    assert_eq!(
+        solve_text("(1+2i)(3-4i)", Precision::Complex, None),
+        Ok("11 + 2i".into())
+    );
+    assert_eq!(solve_text("i^2", Precision::Complex, None), Ok("-1".into()));
+    assert_eq!(solve_text("-i", Precision::Complex, None), Ok("-i".into()));
+    assert_eq!(
+        solve_text("e^(iπ)", Precision::Complex, None),
+        Ok("-1".into())
+    );
+    assert_eq!(
+        solve_text("3 root -8", Precision::Complex, None),
+        Ok("-2".into())
+    );
+}
+
+#[test]
+fn complex_functions() {
+    assert_eq!(
+        solve_text("ln(-1)", Precision::Complex, None),
+        Ok("3.141592653589793i".into())
+    );
+    assert_eq!(
+        solve_text("(-8)^(1/3)", Precision::Complex, None),
+        Ok("1.0000000000000002 + 1.7320508075688772i".into())
+    );
+    assert_eq!(
+        solve_text("1/0i", Precision::Complex, None),
+        Err(SolveError::DivisionByZero(1))
+    );
+    assert_eq!(
+        solve_text("ln(0)", Precision::Complex, None),
+        Err(SolveError::Domain(0))
+    );
+}
+
+#[test]
+fn complex_display() {
+    let equation = Equation::parse("1+i").unwrap();
+    let answer = solve_answer(
+        &equation,
+        true,
+        None,
+        &Variables::new(),
+        &Functions::new(),
+        &Rates::default(),
+        Precision::Complex,
+    )
+    .unwrap();
+    let polar = AnswerFormat {
+        complex: ComplexDisplay::Polar,
+        degrees: true,
+        ..Default::default()
+    };
+    assert_eq!(answer.format(polar), "1.4142135623730951∠45°");
+
+    let context = Context {
+        ans: Some(&answer),
+        ..Default::default()
+    };
+    assert_eq!(
+        solve_text("Ans×2", Precision::Complex, Some(&context)),
+        Ok("2 + 2i".into())
+    );
+}
+
+#[test]
+fn real_only() {
+    let equation = Equation::parse("√(-4)").unwrap();
+    assert_eq!(solve(&equation, true, 0.0), Err(SolveError::Domain(0)));
+    let equation = Equation::parse("2i").unwrap();
+    assert_eq!(solve(&equation, true, 0.0), Err(SolveError::Domain(1)));
+}
+
+#[test]
+fn implicit_multiplication() {
+    let equation = Equation::parse("-2π").unwrap();
+    assert_eq!(
+        solve(&equation, false, 0.0),
+        Ok(-2.0 * std::f64::consts::PI)
+    );
+    let equation = Equation::parse("-sin(90)").unwrap();
+    assert_eq!(solve(&equation, true, 0.0), Ok(-1.0));
+}
+
+fn exact(text: &str, degrees: bool) -> Option<String> {
+    exact_form(
+        &Equation::parse(text).unwrap(),
+        degrees,
+        None,
+        &Variables::new(),
+        &Functions::new(),
+    )
+}
+
+#[test]
+fn exact_radicals() {
+    assert_eq!(exact("√(8)", true), Some("2√2".into()));
+    assert_eq!(exact("√(1/2)", true), Some("√2/2".into()));
+    assert_eq!(exact("3√(12)-√(3)", true), Some("5√3".into()));
+    assert_eq!(exact("2^(1/2)×2^(1/2)", true), None);
+    assert_eq!(exact("√(2)+√(3)", true), None);
+}
+
+#[test]
+fn exact_trigonometry() {
+    assert_eq!(exact("sin(45)", true), Some("√2/2".into()));
+    assert_eq!(exact("cos(150)", true), Some("-√3/2".into()));
+    assert_eq!(exact("tan(30)", true), Some("√3/3".into()));
+    assert_eq!(exact("sin(π/3)", false), Some("√3/2".into()));
+    assert_eq!(exact("asin(1/2)", false), Some("π/6".into()));
+    assert_eq!(exact("atan(1)", true), None);
+    assert_eq!(exact("sin(20)", true), None);
+    //whole turns are taken off before the quarter turn is added, so these can't overflow
+    assert_eq!(exact("cos(9223372036854775807)", true), None);
+    assert_eq!(exact("tan(9223372036854775807)", true), None);
+    assert_eq!(exact("cos(9223372036854775470)", true), Some("√3/2".into()));
+}
+
+#[test]
+fn exact_pi() {
+    assert_eq!(exact("π/6", true), Some("π/6".into()));
+    assert_eq!(exact("2π^2", true), Some("2π²".into()));
+    assert_eq!(exact("1/(2π)", true), Some("1/(2π)".into()));
+    assert_eq!(exact("1/3", true), Some("1/3".into()));
+    assert_eq!(exact("log(1000)+1/4", true), None);
+    assert_eq!(exact("e", true), None);
+    //Ans is 0 before anything has been solved
+    assert_eq!(exact("log(0)", true), None);
+    assert_eq!(exact("log(Ans)", true), None);
+    assert_eq!(exact("log(-10)", true), None);
+}
+
+#[test]
+fn parse_variables() {
+    assert_eq!(
+        Equation::parse("2x + rate_2").unwrap(),
+        equation![
+            Number("2".into()),
+            Variable("x".into()),
+            Add,
+            Variable("rate_2".into())
+        ]
+    );
+    assert_eq!(
+        Equation::parse("2πr").unwrap(),
+        equation![Number("2".into()), Pi, Variable("r".into())]
+    );
+    assert_eq!(
+        Equation::parse("sin30 + 1E5").unwrap(),
+        Equation::parse("sin(30 + 1E5").unwrap()
+    );
+    assert_eq!(Equation::parse("2x").unwrap().to_ascii(), "2x");
+}
+
+#[test]
+fn assignment() {
+    let (name, equation) = Equation::parse_assignment(" x = 3.5 + 1").unwrap();
+    assert_eq!(name, Some(Target::Variable("x".into())));
+    assert_eq!(equation, Equation::parse("3.5 + 1").unwrap());
+    assert_eq!(
+        Equation::parse_assignment("2 + 2").unwrap(),
+        (None, Equation::parse("2 + 2").unwrap())
+    );
+    assert_eq!(
+        Equation::parse_assignment("pi = 3"),
+        Err(ParseError::ReservedName(0..2))
+    );
+    assert_eq!(
+        Equation::parse_assignment("2x = 3"),
+        Err(ParseError::UnexpectedSymbol(0..2))
+    );
+    assert_eq!(
+        Equation::parse_assignment("y = 2 # 3"),
+        Err(ParseError::UnknownSymbol(6..7))
+    );
+}
+
+#[test]
+fn variables() {
+    let mut context = Context::default();
+    context.variables.insert("x".into(), Answer::Float(3.5));
+    context
+        .variables
+        .insert("half".into(), Answer::Rational("1/2".parse().unwrap()));
+    assert_eq!(
+        solve_text("2x", Precision::Float, Some(&context)),
+        Ok("7".into())
+    );
+    assert_eq!(
+        solve_text("x^2 - half", Precision::Float, Some(&context)),
+        Ok("11.75".into())
+    );
+    assert_eq!(
+        solve_text("-x", Precision::Float, Some(&context)),
+        Ok("-3.5".into())
+    );
+    assert_eq!(
+        solve_text("x + y", Precision::Float, Some(&context)),
+        Err(SolveError::UnknownVariable(2))
+    );
+}
+
+fn define(functions: &mut Functions, text: &str) {
+    let (target, body) = Equation::parse_assignment(text).unwrap();
+    let Some(Target::Function(name, parameters)) = target else {
+        panic!("{text} doesn't define a function");
+    };
+    functions.insert(name, Function { parameters, body });
+}
+
+#[test]
+fn parse_functions() {
+    assert_eq!(
+        Equation::parse("f(2, -3)").unwrap(),
+        equation![
+            Call("f".into()),
+            Number("2".into()),
+            Comma,
+            Number("-3".into()),
+            ClosingParenthesis
+        ]
+    );
+    assert_eq!(
+        Equation::parse("x (2)").unwrap(),
+        equation![
+            Variable("x".into()),
+            OpeningParenthesis,
+            Number("2".into()),
+            ClosingParenthesis
+        ]
+    );
+    assert_eq!(
+        Equation::parse("2, 3"),
+        Err(ParseError::UnexpectedSymbol(1..2))
+    );
+    assert_eq!(
+        Equation::parse("sin(1, 2)"),
+        Err(ParseError::UnexpectedSymbol(5..6))
+    );
+
+    let equation = Equation::parse("hyp(3, 4").unwrap();
+    assert_eq!(equation.to_text(), "hyp(3, 4)");
+    assert_eq!(equation.to_ascii(), "hyp(3,4");
+    assert_eq!(equation.to_latex(), r"\operatorname{hyp}\left(3, 4\right)");
+
+    assert_eq!(
+        Equation::parse_assignment("f(x, y) = x^2 + y").unwrap().0,
+        Some(Target::Function("f".into(), vec!["x".into(), "y".into()]))
+    );
+    assert_eq!(
+        Equation::parse_assignment("f(x, x) = x"),
+        Err(ParseError::UnexpectedSymbol(0..7))
+    );
+    assert_eq!(
+        Equation::parse_assignment("sin(x) = x"),
+        Err(ParseError::ReservedName(0..3))
+    );
+}
+
+#[test]
+fn functions() {
+    let mut context = Context::default();
+    define(&mut context.functions, "f(x, y) = x^2 + y");
+    define(&mut context.functions, "hyp(a, b) = sqrt(a^2 + b^2)");
+    define(&mut context.functions, "g(x) = 2f(x, 1)");
+    define(&mut context.functions, "loop(x) = loop(x)");
+    assert_eq!(
+        solve_text("f(2, 3)", Precision::Float, Some(&context)),
+        Ok("7".into())
+    );
+    assert_eq!(
+        solve_text("1 + hyp(3, 4)2", Precision::Float, Some(&context)),
+        Ok("11".into())
+    );
+    assert_eq!(
+        solve_text("g(f(1, 1))", Precision::Float, Some(&context)),
+        Ok("10".into())
+    );
+    assert_eq!(
+        solve_text("f(2)", Precision::Float, Some(&context)),
+        Err(SolveError::ArgumentCount(0))
+    );
+    assert_eq!(
+        solve_text("2 + h(2)", Precision::Float, Some(&context)),
+        Err(SolveError::UnknownFunction(2))
+    );
+    assert_eq!(
+        solve_text("loop(1)", Precision::Float, Some(&context)),
+        Err(SolveError::Overflow(0))
+    );
+
+    //parameters hide variables with the same name
+    context.variables.insert("x".into(), Answer::Float(10.0));
+    assert_eq!(
+        solve_text("f(2, x)", Precision::Float, Some(&context)),
+        Ok("14".into())
+    );
 }
 
 #[macro_export]
@@ -97,3 +919,1098 @@ macro_rules! equation {
         }
     }
 }
+
+#[test]
+fn nary_functions() {
+    assert_eq!(
+        Equation::parse("log(2, 8)").unwrap(),
+        equation![
+            Log,
+            Number("2".into()),
+            Comma,
+            Number("8".into()),
+            ClosingParenthesis
+        ]
+    );
+    assert_eq!(
+        solve_text("max(3, -1, 7) - min(4, 2)", Precision::Float, None),
+        Ok("5".into())
+    );
+    assert_eq!(
+        solve_text("gcd(12, 18) + lcm(4, 6)", Precision::Float, None),
+        Ok("18".into())
+    );
+    assert_eq!(
+        solve_text("nCr(5, 2) + nPr(5, 2)", Precision::Float, None),
+        Ok("30".into())
+    );
+    assert_eq!(
+        solve_text("log(2, 8)", Precision::Float, None),
+        Ok("3".into())
+    );
+    assert_eq!(
+        solve_text("round(2.567, 2)", Precision::Float, None),
+        Ok("2.57".into())
+    );
+    assert_eq!(
+        solve_text("mod(-7, 3)", Precision::Float, None),
+        Ok("2".into())
+    );
+    assert_eq!(
+        solve_text("2 + nCr(5)", Precision::Float, None),
+        Err(SolveError::ArgumentCount(2))
+    );
+    assert_eq!(
+        solve_text("gcd(2.5, 5)", Precision::Float, None),
+        Err(SolveError::Domain(0))
+    );
+    assert_eq!(
+        solve_text("mod(1, 0)", Precision::Float, None),
+        Err(SolveError::DivisionByZero(0))
+    );
+
+    assert_eq!(
+        solve_text("nCr(100, 50)", Precision::Decimal(40), None).unwrap(),
+        "100891344545564193334812497256"
+    );
+    assert_eq!(
+        solve_text("round(2/3, 4)", Precision::Decimal(30), None).unwrap(),
+        "0.6667"
+    );
+    assert_eq!(
+        solve_text("max(1/3, 1/4) + gcd(6, 4)", Precision::Rational, None).unwrap(),
+        "7/3"
+    );
+    assert_eq!(
+        solve_text("mod(7/2, 1)", Precision::Rational, None).unwrap(),
+        "1/2"
+    );
+}
+
+#[test]
+fn hyperbolic_functions() {
+    assert_eq!(
+        Equation::parse("sinh(1) + arcosh(2)").unwrap(),
+        equation![
+            Sinh,
+            Number("1".into()),
+            ClosingParenthesis,
+            Add,
+            Acosh,
+            Number("2".into()),
+            ClosingParenthesis
+        ]
+    );
+    assert_eq!(
+        solve_text("cosh(0) + tanh(0)", Precision::Float, None),
+        Ok("1".into())
+    );
+    assert_eq!(
+        solve_text("asinh(sinh(2))", Precision::Float, None),
+        Ok("2".into())
+    );
+    assert_eq!(
+        solve_text("acosh(0.5)", Precision::Float, None),
+        Err(SolveError::Domain(0))
+    );
+    assert_eq!(
+        solve_text("atanh(1)", Precision::Float, None),
+        Err(SolveError::Domain(0))
+    );
+
+    assert_eq!(
+        solve_text("sinh(1)", Precision::Decimal(30), None).unwrap(),
+        "1.1752011936438014568823818506"
+    );
+    assert_eq!(
+        solve_text("cosh(1)", Precision::Decimal(30), None).unwrap(),
+        "1.54308063481524377847790562076"
+    );
+    assert_eq!(
+        solve_text("tanh(1000)", Precision::Decimal(30), None).unwrap(),
+        "1"
+    );
+    assert_eq!(
+        solve_text("atanh(0.5)", Precision::Decimal(30), None).unwrap(),
+        "0.549306144334054845697622618461"
+    );
+    assert_eq!(
+        solve_text("asinh(-1)", Precision::Decimal(30), None).unwrap(),
+        "-0.88137358701954302523260932498"
+    );
+}
+
+#[test]
+fn special_functions() {
+    assert_eq!(
+        Equation::parse("beta(2, 3)").unwrap(),
+        equation![
+            Beta,
+            Number("2".into()),
+            Comma,
+            Number("3".into()),
+            ClosingParenthesis
+        ]
+    );
+    let close = |text: &str, expected: f64| {
+        let value: f64 = solve_text(text, Precision::Float, None)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!((value - expected).abs() < 1e-9, "{text} is {value}");
+    };
+    close("erf(1)", 0.8427007929497149);
+    close("erfc(1)", 0.1572992070502851);
+    close("gamma(0.5)^2", std::f64::consts::PI);
+    close("lgamma(10)", 12.801827480081469);
+    close("lgamma(-0.5)", 1.2655121234846454);
+    close("beta(2, 3)", 1.0 / 12.0);
+    close("digamma(1)", -0.5772156649015329);
+    close("normcdf(1.96)", 0.9750021048517795);
+    close("norminv(0.975)", 1.959963984540054);
+
+    assert_eq!(
+        solve_text("gamma(-2)", Precision::Float, None),
+        Err(SolveError::Domain(0))
+    );
+    assert_eq!(
+        solve_text("digamma(0)", Precision::Float, None),
+        Err(SolveError::Domain(0))
+    );
+    assert_eq!(
+        solve_text("beta(-1, 2)", Precision::Float, None),
+        Err(SolveError::Domain(0))
+    );
+    assert_eq!(
+        solve_text("norminv(1)", Precision::Float, None),
+        Err(SolveError::Domain(0))
+    );
+    assert_eq!(
+        solve_text("gamma(200)", Precision::Float, None),
+        Err(SolveError::Overflow(0))
+    );
+
+    assert_eq!(
+        solve_text("gamma(30)", Precision::Decimal(40), None).unwrap(),
+        "8841761993739701954543616000000"
+    );
+}
+
+#[test]
+fn statistics() {
+    assert_eq!(parse_values("1, 2.5\n-3;4"), Ok(vec![1.0, 2.5, -3.0, 4.0]));
+    assert_eq!(
+        parse_values("1, x, 3"),
+        Err(ParseError::UnknownSymbol(3..4))
+    );
+
+    let summary = Summary::new(&parse_values("2 4 4 4 5 5 7 9").unwrap()).unwrap();
+    assert_eq!(summary.count, 8);
+    assert_eq!(summary.sum, 40.0);
+    assert_eq!(summary.mean, 5.0);
+    assert_eq!(summary.median, 4.5);
+    assert_eq!(summary.modes, vec![4.0]);
+    assert_eq!(summary.population_std_dev, 2.0);
+    assert_eq!(summary.population_variance, 4.0);
+    assert_eq!(summary.variance, Some(32.0 / 7.0));
+    assert_eq!((summary.min, summary.max), (2.0, 9.0));
+    assert!(summary.lower_quartile <= 4.0 && summary.upper_quartile >= 5.0);
+
+    let single = Summary::new(&[3.0]).unwrap();
+    assert_eq!(single.variance, None);
+    assert!(single.modes.is_empty());
+    assert!(Summary::new(&[]).is_none());
+
+    //the results work like variables in equations
+    let context = Context {
+        variables: summary.variables(),
+        ..Default::default()
+    };
+    assert_eq!(
+        solve_text("(maxx - minx) / sd + n", Precision::Float, Some(&context)),
+        Ok(format_number(7.0 / (32.0f64 / 7.0).sqrt() + 8.0))
+    );
+}
+
+#[test]
+fn distributions() {
+    let close = |distribution: Distribution, parameters: &[f64], query, x, expected: f64| {
+        let value = distribution.evaluate(parameters, query, x).unwrap();
+        assert!(
+            (value - expected).abs() < 1e-9,
+            "{distribution:?} {query:?} at {x} is {value}"
+        );
+    };
+    close(
+        Distribution::Normal,
+        &[0.0, 1.0],
+        Query::Density,
+        0.0,
+        0.3989422804014327,
+    );
+    close(
+        Distribution::Normal,
+        &[10.0, 2.0],
+        Query::Cumulative,
+        12.0,
+        0.8413447460685429,
+    );
+    close(
+        Distribution::Normal,
+        &[0.0, 1.0],
+        Query::Inverse,
+        0.975,
+        1.959963984540054,
+    );
+    close(
+        Distribution::StudentsT,
+        &[10.0],
+        Query::Inverse,
+        0.975,
+        2.2281388519649385,
+    );
+    close(
+        Distribution::ChiSquared,
+        &[2.0],
+        Query::Cumulative,
+        2.0,
+        0.6321205588285577,
+    );
+    close(
+        Distribution::ChiSquared,
+        &[3.0],
+        Query::Inverse,
+        0.95,
+        7.814727903251178,
+    );
+    close(
+        Distribution::Exponential,
+        &[2.0],
+        Query::Inverse,
+        0.5,
+        0.34657359027997264,
+    );
+    close(
+        Distribution::Binomial,
+        &[10.0, 0.5],
+        Query::Density,
+        5.0,
+        0.24609375,
+    );
+    close(
+        Distribution::Binomial,
+        &[10.0, 0.5],
+        Query::Density,
+        5.5,
+        0.0,
+    );
+    close(
+        Distribution::Poisson,
+        &[2.0],
+        Query::Cumulative,
+        1.5,
+        0.40600584970983794,
+    );
+    close(Distribution::Poisson, &[2.0], Query::Inverse, 0.5, 2.0);
+
+    assert_eq!(
+        Distribution::Normal.evaluate(&[0.0, -1.0], Query::Density, 0.0),
+        Err(SolveError::Domain(0))
+    );
+    assert_eq!(
+        Distribution::Binomial.evaluate(&[2.5, 0.5], Query::Density, 1.0),
+        Err(SolveError::Domain(0))
+    );
+    assert_eq!(
+        Distribution::Normal.evaluate(&[0.0, 1.0], Query::Inverse, 1.0),
+        Err(SolveError::Domain(0))
+    );
+}
+
+#[test]
+fn programmer() {
+    assert_eq!(parse_integer("0xFF"), Some(255));
+    assert_eq!(parse_integer("-0b101"), Some(-5));
+    assert_eq!(parse_integer("0o17"), Some(15));
+    assert_eq!(parse_integer("0xFFFFFFFFFFFFFFFF"), Some(-1));
+    assert_eq!(parse_integer("0b2"), None);
+    assert_eq!(
+        format_integer(255, Radix::Hexadecimal, Word::default()),
+        "0xFF"
+    );
+    assert_eq!(
+        format_integer(-1, Radix::Octal, Word::default()),
+        "0o1777777777777777777777"
+    );
+    assert_eq!(format_integer(5, Radix::Binary, Word::default()), "0b101");
+
+    let equation = Equation::parse("0xff + 0b1").unwrap();
+    assert_eq!(
+        equation,
+        equation![Number("0xFF".into()), Add, Number("0b1".into())]
+    );
+    assert_eq!(equation.to_text(), "0xFF + 0b1");
+
+    let mut equation = Equation::new();
+    for digit in ["0x0", "0xF", "0xA"] {
+        assert!(equation.try_push(Number(digit.into())));
+    }
+    assert!(!equation.try_push(Number(".".into())));
+    assert!(!equation.try_push(Number("0b1".into())));
+    assert_eq!(equation.to_text(), "0xFA");
+    equation.backspace();
+    equation.backspace();
+    assert!(equation.is_empty());
+
+    assert_eq!(
+        solve_text(
+            "0xFF + 0b1",
+            Precision::Integer(Radix::Hexadecimal, Word::default()),
+            None
+        ),
+        Ok("0x100".into())
+    );
+    assert_eq!(
+        solve_text(
+            "7 / 2",
+            Precision::Integer(Radix::Decimal, Word::default()),
+            None
+        ),
+        Ok("3".into())
+    );
+    assert_eq!(
+        solve_text(
+            "-7 / 2",
+            Precision::Integer(Radix::Decimal, Word::default()),
+            None
+        ),
+        Ok("-3".into())
+    );
+    assert_eq!(
+        solve_text(
+            "mod(-7, 3)",
+            Precision::Integer(Radix::Decimal, Word::default()),
+            None
+        ),
+        Ok("2".into())
+    );
+    assert_eq!(
+        solve_text(
+            "20!",
+            Precision::Integer(Radix::Decimal, Word::default()),
+            None
+        ),
+        Ok("2432902008176640000".into())
+    );
+    assert_eq!(
+        solve_text(
+            "21!",
+            Precision::Integer(Radix::Decimal, Word::default()),
+            None
+        ),
+        Ok("-4249290049419214848".into())
+    );
+    assert_eq!(
+        solve_text(
+            "1 / 0",
+            Precision::Integer(Radix::Decimal, Word::default()),
+            None
+        ),
+        Err(SolveError::DivisionByZero(1))
+    );
+    assert_eq!(
+        solve_text(
+            "2 ^ -1",
+            Precision::Integer(Radix::Decimal, Word::default()),
+            None
+        ),
+        Err(SolveError::Domain(1))
+    );
+    assert_eq!(
+        solve(&Equation::parse("0x10 / 0o10").unwrap(), true, 0.0),
+        Ok(2.0)
+    );
+}
+
+#[test]
+fn bitwise() {
+    let byte = Word {
+        bits: 8,
+        signed: false,
+    };
+    let signed_byte = Word {
+        bits: 8,
+        signed: true,
+    };
+    assert_eq!(
+        Equation::parse("0xF0 | 0x0F & 3 << 1").unwrap(),
+        equation![
+            Number("0xF0".into()),
+            Or,
+            Number("0x0F".into()),
+            And,
+            Number("3".into()),
+            LeftShift,
+            Number("1".into())
+        ]
+    );
+    assert_eq!(
+        Equation::parse("~5 xor 1").unwrap().to_text(),
+        "NOT(5 XOR 1)"
+    );
+
+    let word = Word::default();
+    //shifts bind tighter than and, which binds tighter than xor and or
+    assert_eq!(
+        solve_text(
+            "0xF0 | 0x0F & 3 << 1",
+            Precision::Integer(Radix::Hexadecimal, word),
+            None
+        ),
+        Ok("0xF6".into())
+    );
+    assert_eq!(
+        solve_text("1 + 1 << 2", Precision::Integer(Radix::Decimal, word), None),
+        Ok("8".into())
+    );
+    assert_eq!(
+        solve_text(
+            "6 xor 3 or 8",
+            Precision::Integer(Radix::Decimal, word),
+            None
+        ),
+        Ok("13".into())
+    );
+    assert_eq!(
+        solve_text("not(0)", Precision::Integer(Radix::Decimal, word), None),
+        Ok("-1".into())
+    );
+    assert_eq!(
+        solve_text("not(0)", Precision::Integer(Radix::Decimal, byte), None),
+        Ok("255".into())
+    );
+    assert_eq!(
+        solve_text(
+            "0x81 rol 1",
+            Precision::Integer(Radix::Hexadecimal, byte),
+            None
+        ),
+        Ok("0x3".into())
+    );
+    assert_eq!(
+        solve_text(
+            "0x81 ror 9",
+            Precision::Integer(Radix::Hexadecimal, byte),
+            None
+        ),
+        Ok("0xC0".into())
+    );
+    assert_eq!(
+        solve_text(
+            "-16 >> 2",
+            Precision::Integer(Radix::Decimal, signed_byte),
+            None
+        ),
+        Ok("-4".into())
+    );
+    assert_eq!(
+        solve_text(
+            "0xF0 >> 2",
+            Precision::Integer(Radix::Hexadecimal, byte),
+            None
+        ),
+        Ok("0x3C".into())
+    );
+    assert_eq!(
+        solve_text(
+            "0xFF",
+            Precision::Integer(Radix::Decimal, signed_byte),
+            None
+        ),
+        Ok("-1".into())
+    );
+    assert_eq!(
+        solve_text("1 << -1", Precision::Integer(Radix::Decimal, word), None),
+        Err(SolveError::Domain(1))
+    );
+
+    let overflowed = |text: &str, word: Word| match solve_answer(
+        &Equation::parse(text).unwrap(),
+        true,
+        None,
+        &Variables::new(),
+        &Functions::new(),
+        &Rates::default(),
+        Precision::Integer(Radix::Decimal, word),
+    ) {
+        Ok(Answer::Integer {
+            value, overflowed, ..
+        }) => (word.value(value as u64), overflowed),
+        answer => panic!("{answer:?}"),
+    };
+    assert_eq!(overflowed("255 + 1", byte), (0, true));
+    assert_eq!(overflowed("127 + 1", signed_byte), (-128, true));
+    assert_eq!(overflowed("0xFF << 4", byte), (0xF0, false));
+    assert_eq!(overflowed("0 - 1", byte), (255, true));
+    assert_eq!(overflowed("2 ^ 64", word), (0, true));
+    assert_eq!(overflowed("100!", word), (0, true));
+    assert_eq!(
+        overflowed(
+            "18446744073709551615",
+            Word {
+                bits: 64,
+                signed: false
+            }
+        ),
+        (u64::MAX as i128, false)
+    );
+}
+
+#[test]
+fn unsigned_words() {
+    let word = Word {
+        bits: 64,
+        signed: false,
+    };
+    //products of the biggest values don't fit in an i128 before they wrap
+    assert_eq!(
+        solve_text(
+            "0xFFFFFFFFFFFFFFFF × 0xFFFFFFFFFFFFFFFF",
+            Precision::Integer(Radix::Hexadecimal, word),
+            None
+        ),
+        Ok("0x1".into())
+    );
+    assert_eq!(
+        solve_text(
+            "0xFFFFFFFFFFFFFFFF ^ 2",
+            Precision::Integer(Radix::Hexadecimal, word),
+            None
+        ),
+        Ok("0x1".into())
+    );
+    assert_eq!(
+        solve_text(
+            "0xFFFFFFFF × 0xFFFFFFFF",
+            Precision::Integer(Radix::Hexadecimal, word),
+            None
+        ),
+        Ok("0xFFFFFFFE00000001".into())
+    );
+    assert_eq!(
+        solve_text(
+            "lcm(0xFFFFFFFFFFFFFFFF, 0xFFFFFFFFFFFFFFFE, 0xFFFFFFFFFFFFFFFD)",
+            Precision::Integer(Radix::Hexadecimal, word),
+            None
+        ),
+        Ok("0xFFFFFFFFFFFFFFFA".into())
+    );
+    assert_eq!(
+        solve_text(
+            "lcm(4, 6, 10)",
+            Precision::Integer(Radix::Decimal, word),
+            None
+        ),
+        Ok("60".into())
+    );
+
+    let answer = solve_answer(
+        &Equation::parse("0xFFFFFFFFFFFFFFFF ^ 3").unwrap(),
+        true,
+        None,
+        &Variables::new(),
+        &Functions::new(),
+        &Rates::default(),
+        Precision::Integer(Radix::Decimal, word),
+    );
+    assert_eq!(
+        answer,
+        Ok(Answer::Integer {
+            value: -1,
+            radix: Radix::Decimal,
+            word,
+            overflowed: true,
+        })
+    );
+}
+
+#[test]
+fn integer_division() {
+    assert_eq!(
+        Equation::parse("7 mod 3 + mod(7, 3)").unwrap(),
+        equation![
+            Number("7".into()),
+            Modulo,
+            Number("3".into()),
+            Add,
+            Mod,
+            Number("7".into()),
+            Comma,
+            Number("3".into()),
+            ClosingParenthesis
+        ]
+    );
+    assert_eq!(
+        Equation::parse("7 // 2").unwrap(),
+        Equation::parse("7 div 2").unwrap()
+    );
+    assert_eq!(
+        Equation::parse("floor(2.5) + abs(-1)").unwrap().to_text(),
+        "floor(2.5) + abs(-1)"
+    );
+
+    //mod and div bind like multiplication
+    assert_eq!(
+        solve_text("2 + 7 mod 3", Precision::Float, None),
+        Ok("3".into())
+    );
+    assert_eq!(
+        solve_text("17 div 5 + 1", Precision::Float, None),
+        Ok("4".into())
+    );
+    assert_eq!(
+        solve_text("-7 div 2", Precision::Float, None),
+        Ok("-4".into())
+    );
+    assert_eq!(
+        solve_text("-7 mod 2", Precision::Float, None),
+        Ok("1".into())
+    );
+    //and go left to right with ×, ÷ and each other
+    assert_eq!(
+        solve_text("2 × 3 mod 4", Precision::Float, None),
+        Ok("2".into())
+    );
+    assert_eq!(
+        solve_text("7 mod 3 × 2", Precision::Float, None),
+        Ok("2".into())
+    );
+    assert_eq!(
+        solve_text("7 div 2 × 2", Precision::Float, None),
+        Ok("6".into())
+    );
+    assert_eq!(
+        solve_text("12 ÷ 3 div 2", Precision::Float, None),
+        Ok("2".into())
+    );
+    assert_eq!(
+        solve_text("17 mod 5 div 2", Precision::Float, None),
+        Ok("1".into())
+    );
+    assert_eq!(
+        solve_text("6 ÷ 2 × 3", Precision::Float, None),
+        Ok("9".into())
+    );
+    //mod after a value is the operator even with a parenthesis after it
+    assert_eq!(
+        solve_text("7 mod (1+2)", Precision::Float, None),
+        Ok("1".into())
+    );
+    assert_eq!(
+        solve_text("2 × mod(7, 3)", Precision::Float, None),
+        Ok("2".into())
+    );
+    assert_eq!(
+        solve_text("(mod(7, 4))", Precision::Float, None),
+        Ok("3".into())
+    );
+    assert_eq!(
+        solve_text(
+            "floor(-2.5) + ceil(2.1) + trunc(-2.7)",
+            Precision::Float,
+            None
+        ),
+        Ok("-2".into())
+    );
+    assert_eq!(
+        solve_text("abs(-3) × sign(-0.5) + sign(0)", Precision::Float, None),
+        Ok("-3".into())
+    );
+    assert_eq!(
+        solve_text("5 div 0", Precision::Float, None),
+        Err(SolveError::DivisionByZero(1))
+    );
+
+    assert_eq!(
+        solve_text("(22/7) div (1/2)", Precision::Rational, None).unwrap(),
+        "6"
+    );
+    assert_eq!(
+        solve_text("floor(-7/2) + abs(-1/3)", Precision::Rational, None).unwrap(),
+        "-11/3"
+    );
+    assert_eq!(
+        solve_text("ceil(10/3) × sign(-2)", Precision::Decimal(30), None).unwrap(),
+        "-4"
+    );
+    assert_eq!(
+        solve_text(
+            "-7 div 2",
+            Precision::Integer(Radix::Decimal, Word::default()),
+            None
+        ),
+        Ok("-4".into())
+    );
+    assert_eq!(
+        solve_text(
+            "-7 / 2",
+            Precision::Integer(Radix::Decimal, Word::default()),
+            None
+        ),
+        Ok("-3".into())
+    );
+}
+
+#[test]
+fn saved_answers() {
+    let answers = [
+        Answer::Float(2.5),
+        Answer::Decimal {
+            value: "1.25".parse().unwrap(),
+            digits: 30,
+        },
+        Answer::Rational("-1/3".parse().unwrap()),
+        Answer::Complex(Complex64::new(1.0, -2.0)),
+        Answer::Integer {
+            value: -1,
+            radix: Radix::Hexadecimal,
+            word: Word {
+                bits: 64,
+                signed: false,
+            },
+            overflowed: true,
+        },
+        Answer::Quantity {
+            value: 3.5,
+            unit: "mi".into(),
+        },
+    ];
+    for answer in answers {
+        let text = ron::to_string(&answer).unwrap();
+        assert_eq!(ron::from_str::<Answer>(&text).unwrap(), answer, "{text}");
+    }
+
+    //history and variables saved before answers were tagged
+    let history = parse_saved(
+        r#"[((list: [Number("2")]), 2.0)]"#,
+        |history: Vec<(Equation, f64)>| {
+            history
+                .into_iter()
+                .map(|(equation, value)| (equation, Answer::Float(value)))
+                .collect::<Vec<_>>()
+        },
+    );
+    assert_eq!(
+        history,
+        Some(vec![(equation![Number("2".into())], Answer::Float(2.0))])
+    );
+    let variables = parse_saved(r#"{"x": 3.5}"#, |variables: BTreeMap<String, f64>| {
+        variables
+            .into_iter()
+            .map(|(name, value)| (name, Answer::Float(value)))
+            .collect::<Variables>()
+    });
+    assert_eq!(variables.unwrap()["x"], Answer::Float(3.5));
+}
+
+#[test]
+fn units() {
+    assert_eq!(
+        Equation::parse("5 km + 300 m in mi").unwrap(),
+        equation![
+            Number("5".into()),
+            Unit("km".into()),
+            Add,
+            Number("300".into()),
+            Unit("m".into()),
+            In,
+            Unit("mi".into())
+        ]
+    );
+    assert_eq!(
+        Equation::parse("min(2, 3) + 4 min").unwrap(),
+        equation![
+            Min,
+            Number("2".into()),
+            Comma,
+            Number("3".into()),
+            ClosingParenthesis,
+            Add,
+            Number("4".into()),
+            Unit("min".into())
+        ]
+    );
+    let equation = Equation::parse("60 mph × 2h to km").unwrap();
+    assert_eq!(equation.to_text(), "60 mph × 2 h in km");
+    assert_eq!(equation.to_ascii(), "60 mph*2 h in km");
+    assert_eq!(Equation::parse(&equation.to_ascii()).unwrap(), equation);
+
+    assert_eq!(
+        solve_text("5 km + 300 m", Precision::Float, None),
+        Ok("5.3 km".into())
+    );
+    assert_eq!(
+        solve_text("5 km + 300 m in m", Precision::Float, None),
+        Ok("5300 m".into())
+    );
+    assert_eq!(
+        solve_text("round(5 km + 300 m in mi, 3)", Precision::Float, None),
+        Err(SolveError::UnsupportedOperator(6))
+    );
+    assert_eq!(
+        solve_text("60 mph × 2 h in mi", Precision::Float, None),
+        Ok("120 mi".into())
+    );
+    assert_eq!(
+        solve_text("60 mph × 2 h", Precision::Float, None),
+        Ok("193121.28 m".into())
+    );
+    assert_eq!(
+        solve_text("3 ft × 2 ft in ft^2", Precision::Float, None),
+        Ok("6 ft^2".into())
+    );
+    assert_eq!(
+        solve_text("1 GB / 8 MB", Precision::Float, None),
+        Ok("125".into())
+    );
+    assert_eq!(
+        solve_text("10 m / 4 s", Precision::Float, None),
+        Ok("2.5 m/s".into())
+    );
+    assert_eq!(
+        solve_text("2 / 4 s", Precision::Float, None),
+        Ok("0.5 s⁻¹".into())
+    );
+    assert_eq!(
+        solve_text("√(16 m^2)", Precision::Float, None),
+        Ok("4 m".into())
+    );
+    assert_eq!(
+        solve_text("90 min in h", Precision::Float, None),
+        Ok("1.5 h".into())
+    );
+    assert_eq!(
+        solve_text("12 in 4", Precision::Float, None),
+        Ok("3".into())
+    );
+
+    assert_eq!(
+        solve_text("5 km + 3 s", Precision::Float, None),
+        Err(SolveError::IncompatibleUnits(2))
+    );
+    assert_eq!(
+        solve_text("5 km in kg", Precision::Float, None),
+        Err(SolveError::IncompatibleUnits(2))
+    );
+    assert_eq!(
+        solve_text("sin(2 m)", Precision::Float, None),
+        Err(SolveError::IncompatibleUnits(0))
+    );
+    assert_eq!(
+        solve_text("1 m in 2 + 3 s", Precision::Float, None),
+        Err(SolveError::IncompatibleUnits(4))
+    );
+    assert_eq!(
+        SolveError::IncompatibleUnits(0).to_string(),
+        "Incompatible units"
+    );
+
+    //variables and parameters hide units with the same name
+    let mut context = Context::default();
+    context.variables.insert("h".into(), Answer::Float(2.0));
+    assert_eq!(
+        solve_text("3h", Precision::Float, Some(&context)),
+        Ok("6".into())
+    );
+    define(&mut context.functions, "area(m) = m^2");
+    define(&mut context.functions, "walk(t) = t × 5 kph");
+    assert_eq!(
+        solve_text("area(3)", Precision::Float, Some(&context)),
+        Ok("9".into())
+    );
+    assert_eq!(
+        solve_text("walk(30 min) in m", Precision::Float, Some(&context)),
+        Ok("2500 m".into())
+    );
+
+    let equation = Equation::parse("5 km + 300 m in m").unwrap();
+    assert_eq!(solve(&equation, false, 0.0), Ok(5300.0));
+}
+
+#[test]
+fn continue_units() {
+    let solve_float = |equation: &Equation| {
+        solve_answer(
+            equation,
+            true,
+            None,
+            &Variables::new(),
+            &Functions::new(),
+            &Rates::default(),
+            Precision::Float,
+        )
+    };
+    let answer = solve_float(&Equation::parse("5 km in mi").unwrap()).unwrap();
+    let mut equation = answer.to_equation();
+    assert_eq!(equation.to_text(), "3.1068559611866697 mi");
+    equation.try_push_text("+ 1 mi").unwrap();
+    assert_eq!(
+        solve_float(&equation).map(|answer| answer.to_string()),
+        Ok("4.10685596118667 mi".into())
+    );
+    let mut equation = answer.to_equation();
+    equation.try_push_text("+ 1").unwrap();
+    assert_eq!(
+        solve_float(&equation),
+        Err(SolveError::IncompatibleUnits(2))
+    );
+
+    let answer = solve_float(&Equation::parse("10 m / 4 s in km/h").unwrap()).unwrap();
+    assert_eq!(answer.to_equation().to_text(), "9 km ÷ h");
+    //base units with powers can't be typed back, so only the number carries on
+    let answer = Answer::Quantity {
+        value: 2.0,
+        unit: "kg·m²/s²".into(),
+    };
+    assert_eq!(answer.to_equation(), equation![Rnd("2".into())]);
+}
+
+#[test]
+fn stored_units() {
+    let ans = Answer::Quantity {
+        value: 5.0,
+        unit: "km".into(),
+    };
+    let context = Context {
+        ans: Some(&ans),
+        ..Default::default()
+    };
+    assert_eq!(
+        solve_text("Ans + 1 km", Precision::Float, Some(&context)),
+        Ok("6 km".into())
+    );
+    assert_eq!(
+        solve_text("Ans^2 in m^2", Precision::Float, Some(&context)),
+        Ok("25000000 m^2".into())
+    );
+    assert_eq!(
+        solve_text("Ans + 1", Precision::Float, Some(&context)),
+        Err(SolveError::IncompatibleUnits(1))
+    );
+
+    let mut context = Context::default();
+    context.variables.insert(
+        "d".into(),
+        Answer::Quantity {
+            value: 5.0,
+            unit: "km".into(),
+        },
+    );
+    context.variables.insert(
+        "v".into(),
+        Answer::Quantity {
+            value: 60.0,
+            unit: "km/h".into(),
+        },
+    );
+    assert_eq!(
+        solve_text("d + 300 m", Precision::Float, Some(&context)),
+        Ok("5.3 km".into())
+    );
+    assert_eq!(
+        solve_text("d / v in min", Precision::Float, Some(&context)),
+        Ok("5 min".into())
+    );
+    assert_eq!(
+        solve_text("2d", Precision::Float, Some(&context)),
+        Ok("10 km".into())
+    );
+}
+
+#[test]
+fn currency() {
+    let context = Context {
+        rates: parse_json(
+            r#"{"base": "USD", "timestamp": 1760745600, "rates": {"EUR": 0.8, "GBP": 0.5}}"#,
+        )
+        .unwrap(),
+        ..Default::default()
+    };
+    let rates = &context.rates;
+    assert_eq!(rates.updated.as_deref(), Some("2025-10-18 00:00 UTC"));
+    assert_eq!(rates.value("USD"), Some(1.0));
+    assert_eq!(
+        parse_csv("currency,rate\nbase,USD\n# from the bank\ntimestamp,2025-10-18 00:00 UTC\nEUR,0.8\nGBP;0.5\n"),
+        Ok(rates.clone())
+    );
+    assert_eq!(
+        parse_json(r#"{"date": "2025-10-18", "rates": {"EUR": 0.8}}"#)
+            .unwrap()
+            .updated
+            .as_deref(),
+        Some("2025-10-18")
+    );
+    assert_eq!(format_time(951829620), "2000-02-29 13:07 UTC");
+    assert_eq!(parse_csv("EUR,0.8\nGBP,lots"), Err(RatesError::Invalid(2)));
+    assert_eq!(parse_csv("EUR,-1"), Err(RatesError::BadRate("EUR".into())));
+    assert_eq!(
+        parse_json(r#"{"rates": {"euro": 0.8}}"#),
+        Err(RatesError::BadRate("euro".into()))
+    );
+    assert!(matches!(
+        parse_json("{\n\"rates\": [1, 2]\n}"),
+        Err(RatesError::Invalid(2))
+    ));
+
+    assert_eq!(
+        solve_text("100 USD in EUR", Precision::Float, Some(&context)),
+        Ok("80 EUR".into())
+    );
+    assert_eq!(
+        solve_text("40 EUR to GBP", Precision::Float, Some(&context)),
+        Ok("25 GBP".into())
+    );
+    assert_eq!(
+        solve_text("10 USD + 8 EUR", Precision::Float, Some(&context)),
+        Ok("20 USD".into())
+    );
+    assert_eq!(
+        solve_text("2 h × 5 EUR/h in USD", Precision::Float, Some(&context)),
+        Ok("12.5 USD".into())
+    );
+    assert_eq!(
+        solve_text("100 USD in m", Precision::Float, Some(&context)),
+        Err(SolveError::IncompatibleUnits(2))
+    );
+    assert_eq!(
+        solve_text("5 JPY", Precision::Float, Some(&context)),
+        Err(SolveError::UnknownCurrency(1))
+    );
+    assert_eq!(
+        solve_text("5 USD", Precision::Float, None),
+        Err(SolveError::UnknownCurrency(1))
+    );
+    assert!(Equation::parse("100 USD in EUR")
+        .unwrap()
+        .contains_currency());
+    assert!(!Equation::parse("5 km in mi").unwrap().contains_currency());
+
+    //only ISO 4217 codes are currencies, other capitals are variables
+    assert_eq!(
+        Equation::parse("2ABC + SUM").unwrap(),
+        equation![
+            Number("2".into()),
+            Variable("ABC".into()),
+            Add,
+            Variable("SUM".into())
+        ]
+    );
+    let mut context = Context::default();
+    context.variables.insert("XYZ".into(), Answer::Float(4.0));
+    assert_eq!(
+        solve_text("3 XYZ", Precision::Float, Some(&context)),
+        Ok("12".into())
+    );
+    //codes like BTC can still be in a rates file
+    assert!(parse_json(r#"{"rates": {"BTC": 0.00001, "EUR": 0.8}}"#).is_ok());
+}
@@ -0,0 +1,342 @@
+use crate::calculator::{superscript_char, Equation, Item};
+use crate::currency::{is_currency, Rates};
+use crate::solver::{solve_with, Backend, Fault, Float, Memory, SolveError};
+use Item::*;
+
+//the powers of kg, m, s, K, B and money a quantity is made of, so m/s is [0, 1, -1, 0, 0, 0]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Dimension([i8; 6]);
+
+//money is measured in the base currency of the rates
+const BASE_UNITS: [&str; 6] = ["kg", "m", "s", "K", "B", "¤"];
+
+const LENGTH: Dimension = Dimension([0, 1, 0, 0, 0, 0]);
+const MASS: Dimension = Dimension([1, 0, 0, 0, 0, 0]);
+const TIME: Dimension = Dimension([0, 0, 1, 0, 0, 0]);
+const TEMPERATURE: Dimension = Dimension([0, 0, 0, 1, 0, 0]);
+const INFORMATION: Dimension = Dimension([0, 0, 0, 0, 1, 0]);
+const SPEED: Dimension = Dimension([0, 1, -1, 0, 0, 0]);
+const VOLUME: Dimension = Dimension([0, 3, 0, 0, 0, 0]);
+const ENERGY: Dimension = Dimension([1, 2, -2, 0, 0, 0]);
+const POWER: Dimension = Dimension([1, 2, -3, 0, 0, 0]);
+const FORCE: Dimension = Dimension([1, 1, -2, 0, 0, 0]);
+const PRESSURE: Dimension = Dimension([1, -1, -2, 0, 0, 0]);
+const FREQUENCY: Dimension = Dimension([0, 0, -1, 0, 0, 0]);
+const MONEY: Dimension = Dimension([0, 0, 0, 0, 0, 1]);
+
+//each unit's size in the base units
+const UNITS: &[(&str, f64, Dimension)] = &[
+    ("m", 1.0, LENGTH),
+    ("km", 1000.0, LENGTH),
+    ("cm", 0.01, LENGTH),
+    ("mm", 0.001, LENGTH),
+    ("mi", 1609.344, LENGTH),
+    ("yd", 0.9144, LENGTH),
+    ("ft", 0.3048, LENGTH),
+    //in is the conversion
+    ("inch", 0.0254, LENGTH),
+    ("g", 0.001, MASS),
+    ("kg", 1.0, MASS),
+    ("mg", 1e-6, MASS),
+    ("lb", 0.45359237, MASS),
+    ("oz", 0.028349523125, MASS),
+    ("tonne", 1000.0, MASS),
+    ("s", 1.0, TIME),
+    ("ms", 0.001, TIME),
+    ("min", 60.0, TIME),
+    ("h", 3600.0, TIME),
+    ("day", 86400.0, TIME),
+    ("week", 604800.0, TIME),
+    //julian years of 365.25 days
+    ("year", 31557600.0, TIME),
+    ("K", 1.0, TEMPERATURE),
+    ("B", 1.0, INFORMATION),
+    ("bit", 0.125, INFORMATION),
+    ("KB", 1e3, INFORMATION),
+    ("MB", 1e6, INFORMATION),
+    ("GB", 1e9, INFORMATION),
+    ("mph", 0.44704, SPEED),
+    ("kph", 1000.0 / 3600.0, SPEED),
+    ("L", 0.001, VOLUME),
+    ("mL", 1e-6, VOLUME),
+    ("J", 1.0, ENERGY),
+    ("kJ", 1000.0, ENERGY),
+    ("cal", 4.184, ENERGY),
+    ("kcal", 4184.0, ENERGY),
+    ("W", 1.0, POWER),
+    ("kW", 1000.0, POWER),
+    ("N", 1.0, FORCE),
+    ("Pa", 1.0, PRESSURE),
+    ("Hz", 1.0, FREQUENCY),
+];
+
+//currencies count even when there's no rate for them
+pub fn is_unit(name: &str) -> bool {
+    unit(name).is_some() || is_currency(name)
+}
+
+fn unit(name: &str) -> Option<Quantity> {
+    UNITS
+        .iter()
+        .find(|(unit, ..)| *unit == name)
+        .map(|(_, value, dimension)| Quantity {
+            value: *value,
+            dimension: *dimension,
+        })
+}
+
+impl Dimension {
+    pub fn is_none(self) -> bool {
+        self == Dimension::default()
+    }
+
+    //multiplies when sign is 1 and divides when it's -1, none when a power gets too big
+    fn combine(self, other: Dimension, sign: i32) -> Option<Dimension> {
+        let mut powers = self.0;
+        for (power, other) in powers.iter_mut().zip(other.0) {
+            *power = i8::try_from(*power as i32 + sign * other as i32).ok()?;
+        }
+        Some(Dimension(powers))
+    }
+
+    //none when a power isn't a whole number, like the square root of m
+    fn scale(self, numerator: i32, denominator: i32) -> Option<Dimension> {
+        let mut powers = self.0;
+        for power in &mut powers {
+            let scaled = *power as i32 * numerator;
+            if scaled % denominator != 0 {
+                return None;
+            }
+            *power = i8::try_from(scaled / denominator).ok()?;
+        }
+        Some(Dimension(powers))
+    }
+}
+
+//written with the base units like kg·m²/s², or s⁻¹ when every power is negative
+pub fn format_dimension(dimension: Dimension) -> String {
+    let part = |unit: &str, power: i8| {
+        if power == 1 {
+            unit.to_string()
+        } else {
+            format!(
+                "{unit}{}",
+                power
+                    .to_string()
+                    .chars()
+                    .map(superscript_char)
+                    .collect::<String>()
+            )
+        }
+    };
+    let numerator = BASE_UNITS
+        .iter()
+        .zip(dimension.0)
+        .filter(|(_, power)| *power > 0)
+        .map(|(unit, power)| part(unit, power))
+        .collect::<Vec<_>>();
+    let denominator = BASE_UNITS
+        .iter()
+        .zip(dimension.0)
+        .filter(|(_, power)| *power < 0)
+        .map(|(unit, power)| part(unit, if numerator.is_empty() { power } else { -power }))
+        .collect::<Vec<_>>();
+
+    if numerator.is_empty() {
+        denominator.join("·")
+    } else if denominator.is_empty() {
+        numerator.join("·")
+    } else {
+        format!("{}/{}", numerator.join("·"), denominator.join("·"))
+    }
+}
+
+//a value in the base units
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Quantity {
+    pub value: f64,
+    pub dimension: Dimension,
+}
+
+impl Quantity {
+    fn number(value: f64) -> Self {
+        Quantity {
+            value,
+            dimension: Dimension::default(),
+        }
+    }
+}
+
+//solves with floats while keeping track of units, values can only be added if they measure the same thing
+pub struct Measure<'a> {
+    pub rates: &'a Rates,
+}
+
+impl Measure<'_> {
+    fn unit(&self, name: &str) -> Option<Quantity> {
+        unit(name).or_else(|| {
+            self.rates.value(name).map(|value| Quantity {
+                value,
+                dimension: MONEY,
+            })
+        })
+    }
+
+    //functions like sin and ln only take plain numbers
+    fn dimensionless(&self, values: &[Quantity]) -> Result<(), Fault> {
+        if values.iter().all(|value| value.dimension.is_none()) {
+            Ok(())
+        } else {
+            Err(SolveError::IncompatibleUnits)
+        }
+    }
+}
+
+impl Backend for Measure<'_> {
+    type Value = Quantity;
+
+    fn parse(&self, num: &str) -> Option<Quantity> {
+        Float.parse(num).map(Quantity::number)
+    }
+
+    fn constant(&self, constant: &Item) -> Result<Quantity, Fault> {
+        match constant {
+            Unit(name) if is_currency(name) => self.unit(name).ok_or(SolveError::UnknownCurrency),
+            Unit(name) => self.unit(name).ok_or(SolveError::UnknownVariable),
+            _ => Float.constant(constant).map(Quantity::number),
+        }
+    }
+
+    fn operate(
+        &self,
+        operation: &Item,
+        value1: Quantity,
+        value2: Quantity,
+    ) -> Result<Quantity, Fault> {
+        let dimension = match operation {
+            Add | Subtract if value1.dimension != value2.dimension => {
+                return Err(SolveError::IncompatibleUnits)
+            }
+            Add | Subtract => value1.dimension,
+            Multiply => value1
+                .dimension
+                .combine(value2.dimension, 1)
+                .ok_or(SolveError::Overflow as Fault)?,
+            Divide | IntegerDivide => value1
+                .dimension
+                .combine(value2.dimension, -1)
+                .ok_or(SolveError::Overflow as Fault)?,
+            Power if !value2.dimension.is_none() => return Err(SolveError::IncompatibleUnits),
+            Power if value1.dimension.is_none() => value1.dimension,
+            //m^2 is an area but m^0.5 isn't anything
+            Power if value2.value.fract() == 0.0 => value1
+                .dimension
+                .scale(value2.value.clamp(-1000.0, 1000.0) as i32, 1)
+                .ok_or(SolveError::Overflow as Fault)?,
+            Power => return Err(SolveError::IncompatibleUnits),
+            Nroot if !value1.dimension.is_none() => return Err(SolveError::IncompatibleUnits),
+            Nroot if value2.dimension.is_none() => value2.dimension,
+            Nroot if value1.value.fract() == 0.0 && value1.value != 0.0 => value2
+                .dimension
+                .scale(1, value1.value.clamp(-1000.0, 1000.0) as i32)
+                .ok_or(SolveError::IncompatibleUnits as Fault)?,
+            Nroot => return Err(SolveError::IncompatibleUnits),
+            _ => {
+                self.dimensionless(&[value1, value2])?;
+                value1.dimension
+            }
+        };
+        Ok(Quantity {
+            value: Float.operate(operation, value1.value, value2.value)?,
+            dimension,
+        })
+    }
+
+    fn function(&self, function: &Item, value: Quantity, degrees: bool) -> Result<Quantity, Fault> {
+        let dimension = match function {
+            Abs => value.dimension,
+            Sign => Dimension::default(),
+            Sqrt => value
+                .dimension
+                .scale(1, 2)
+                .ok_or(SolveError::IncompatibleUnits as Fault)?,
+            _ => {
+                self.dimensionless(&[value])?;
+                value.dimension
+            }
+        };
+        Ok(Quantity {
+            value: Float.function(function, value.value, degrees)?,
+            dimension,
+        })
+    }
+
+    fn nary_function(&self, function: &Item, values: Vec<Quantity>) -> Result<Quantity, Fault> {
+        let dimension = match (function, values.as_slice()) {
+            (Min | Max | Mod, [first, ..]) => {
+                if values
+                    .iter()
+                    .any(|value| value.dimension != first.dimension)
+                {
+                    return Err(SolveError::IncompatibleUnits);
+                }
+                first.dimension
+            }
+            _ => {
+                self.dimensionless(&values)?;
+                Dimension::default()
+            }
+        };
+        let values = values.iter().map(|value| value.value).collect();
+        Ok(Quantity {
+            value: Float.nary_function(function, values)?,
+            dimension,
+        })
+    }
+
+    fn factorial(&self, value: Quantity) -> Result<Quantity, Fault> {
+        self.dimensionless(&[value])?;
+        Float.factorial(value.value).map(Quantity::number)
+    }
+}
+
+//the value of a solved equation with units and the unit it's written in, none for plain numbers
+pub fn solve_units(
+    equation: &Equation,
+    degrees: bool,
+    memory: &Memory,
+) -> Result<(f64, Option<String>), SolveError> {
+    let measure = Measure {
+        rates: &memory.rates,
+    };
+    let Some((equation, index, target)) = equation.split_conversion() else {
+        let quantity = solve_with(&measure, equation, degrees, memory)?;
+        if quantity.dimension.is_none() {
+            return Ok((quantity.value, None));
+        }
+        //5 km + 300 m is written in km, the first unit that fits
+        let written = equation.units(memory).into_iter().find_map(|name| {
+            measure
+                .unit(&name)
+                .filter(|unit| unit.dimension == quantity.dimension)
+                .map(|unit| (quantity.value / unit.value, Some(name)))
+        });
+        return Ok(
+            written.unwrap_or_else(|| (quantity.value, Some(format_dimension(quantity.dimension))))
+        );
+    };
+
+    let quantity = solve_with(&measure, &equation, degrees, memory)?;
+    let unit = solve_with(&measure, &target, degrees, memory)
+        .map_err(|error| error.at(error.index() + index + 1))?;
+    if quantity.dimension != unit.dimension {
+        return Err(SolveError::IncompatibleUnits(index));
+    } else if unit.value == 0.0 {
+        return Err(SolveError::DivisionByZero(index));
+    }
+    let name = target.to_ascii().trim().to_string();
+    Ok((
+        quantity.value / unit.value,
+        Some(name).filter(|_| !unit.dimension.is_none()),
+    ))
+}